@@ -1,82 +1,73 @@
 use std::collections::HashSet;
 use std::fs;
 
+pub mod analysis;
+pub mod render;
+
 pub fn run() {
     let sketch = fs::read_to_string("10.txt").unwrap();
     println!("pt1: {:?}", steps_to_farthest_point(&sketch));
     println!("pt2: {:?}", enclosed_tile_count(&sketch));
 }
 
-#[derive(Debug)]
-enum Dir {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dir {
     Up,
     Down,
     Left,
     Right,
 }
 
-fn _remove_non_loop_pipes(
-    sketch: &Vec<Vec<char>>,
-    loop_positions: &HashSet<[usize; 2]>,
-) -> Vec<Vec<char>> {
-    sketch
-        .iter()
-        .enumerate()
-        .map(|(i, row)| {
-            row.iter()
-                .enumerate()
-                .map(|(j, tile)| {
-                    if loop_positions.contains(&[i, j]) {
-                        *tile
-                    } else {
-                        '.'
-                    }
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>()
-}
-
-fn _debug_print_loop(sketch: &Vec<Vec<char>>, loop_positions: &HashSet<[usize; 2]>) {
-    let _debug_sketch = _remove_non_loop_pipes(&sketch, &loop_positions);
-    let _debug_sketch = _debug_sketch
-        .iter()
-        .map(|row| row.iter().collect::<String>())
-        .collect::<Vec<String>>()
-        .join("\n");
-    println!("{_debug_sketch}");
-}
-
 fn steps_to_farthest_point(sketch: &str) -> usize {
     let mut sketch: Vec<Vec<char>> = sketch.lines().map(|l| l.chars().collect()).collect();
     let (s_pos, s_symbol) = find_start(&sketch);
     sketch[s_pos[0]][s_pos[1]] = s_symbol;
     let loop_positions = loop_positions(&sketch, s_pos);
 
-    // _debug_print_loop(&sketch, &loop_positions);
-
     loop_positions.len() / 2
 }
 
 fn enclosed_tile_count(sketch: &str) -> u64 {
+    let (sketch, path) = main_loop(sketch);
+    let vertices = corners(&sketch, &path);
+    let boundary = path.len() as i64;
+
+    // Pick's theorem: area = interior + boundary / 2 - 1
+    let interior = shoelace_area(&vertices) - boundary / 2 + 1;
+
+    interior as u64
+}
+
+pub fn loop_vertices(sketch: &str) -> Vec<[usize; 2]> {
+    let (sketch, path) = main_loop(sketch);
+    corners(&sketch, &path)
+}
+
+fn main_loop(sketch: &str) -> (Vec<Vec<char>>, Vec<[usize; 2]>) {
     let mut sketch: Vec<Vec<char>> = sketch.lines().map(|l| l.chars().collect()).collect();
     let (s_pos, s_symbol) = find_start(&sketch);
     sketch[s_pos[0]][s_pos[1]] = s_symbol;
-    let loop_positions = loop_positions(&sketch, s_pos);
+    let path = loop_path(&sketch, s_pos);
+    (sketch, path)
+}
 
-    // _debug_print_loop(&sketch, &loop_positions);
+fn corners(sketch: &[Vec<char>], path: &[[usize; 2]]) -> Vec<[usize; 2]> {
+    path.iter()
+        .filter(|[i, j]| matches!(sketch[*i][*j], 'F' | '7' | 'J' | 'L'))
+        .copied()
+        .collect()
+}
 
-    let mut count = 0;
-    for i in 0..sketch.len() {
-        for j in 0..sketch[i].len() {
-            if is_enclosed(&sketch, &loop_positions, [i, j]) {
-                // println!("enclosed: {:?}", [i, j]);
-                count += 1;
-            }
-        }
-    }
+fn shoelace_area(vertices: &[[usize; 2]]) -> i64 {
+    let twice_area: i64 = vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|([i_a, j_a], [i_b, j_b])| {
+            (*i_a as i64) * (*j_b as i64) - (*i_b as i64) * (*j_a as i64)
+        })
+        .sum();
 
-    count
+    twice_area.abs() / 2
 }
 
 fn peek(direction: Dir, sketch: &Vec<Vec<char>>, pos: [usize; 2]) -> Option<char> {
@@ -148,6 +139,10 @@ fn find_start(sketch: &Vec<Vec<char>>) -> ([usize; 2], char) {
 }
 
 fn loop_positions(sketch: &Vec<Vec<char>>, s_pos: [usize; 2]) -> HashSet<[usize; 2]> {
+    loop_path(sketch, s_pos).into_iter().collect()
+}
+
+fn loop_path(sketch: &Vec<Vec<char>>, s_pos: [usize; 2]) -> Vec<[usize; 2]> {
     let s_symbol = sketch[s_pos[0]][s_pos[1]];
 
     let mut direction = match s_symbol {
@@ -162,10 +157,10 @@ fn loop_positions(sketch: &Vec<Vec<char>>, s_pos: [usize; 2]) -> HashSet<[usize;
 
     let [mut i, mut j] = s_pos;
 
-    let mut positions = HashSet::new();
+    let mut positions = vec![];
 
     loop {
-        positions.insert([i, j]);
+        positions.push([i, j]);
 
         match direction {
             Dir::Right => j += 1,
@@ -201,29 +196,6 @@ fn loop_positions(sketch: &Vec<Vec<char>>, s_pos: [usize; 2]) -> HashSet<[usize;
     positions
 }
 
-fn is_enclosed(
-    sketch: &Vec<Vec<char>>,
-    loop_positions: &HashSet<[usize; 2]>,
-    pos: [usize; 2],
-) -> bool {
-    if loop_positions.contains(&pos) {
-        return false;
-    }
-    let i = pos[0];
-    let mut j = 0;
-    let mut intersect_count = 0;
-    while j < pos[1] {
-        let char = sketch[i][j];
-        if char == '|' || char == 'F' || char == '7' || char == 'S' {
-            if loop_positions.contains(&[i, j]) {
-                intersect_count += 1;
-            }
-        }
-        j += 1
-    }
-    intersect_count % 2 != 0
-}
-
 #[cfg(test)]
 mod steps_to_farthest_point_tests {
     use super::*;
@@ -283,6 +255,33 @@ mod loop_positions_tests {
     }
 }
 
+#[cfg(test)]
+mod loop_vertices_tests {
+    use super::*;
+
+    #[test]
+    fn returns_corners_in_loop_order() {
+        let sketch = "......
+.S--7.
+.|..|.
+.L--J.
+......";
+
+        assert_eq!(vec![[1, 1], [3, 1], [3, 4], [1, 4]], loop_vertices(sketch));
+    }
+
+    #[test]
+    fn skips_straight_start_tile() {
+        let sketch = "......
+.F-S7.
+.|..|.
+.L--J.
+......";
+
+        assert_eq!(vec![[1, 4], [3, 4], [3, 1], [1, 1]], loop_vertices(sketch));
+    }
+}
+
 #[cfg(test)]
 mod enclosed_tile_count_tests {
     use super::*;
@@ -0,0 +1,385 @@
+use super::Dir;
+use std::collections::HashSet;
+
+const SHAPES: [char; 6] = ['|', '-', 'L', 'J', '7', 'F'];
+
+#[derive(Debug, PartialEq)]
+pub enum MazeError {
+    NoStart,
+    MultipleStarts(Vec<[usize; 2]>),
+    UnrecognisedSymbol { pos: [usize; 2], symbol: char },
+    NoStartShape([usize; 2]),
+    AmbiguousStartShape { pos: [usize; 2], shapes: Vec<char> },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConnectionIssue {
+    Dangling {
+        pos: [usize; 2],
+        direction: Dir,
+    },
+    Mismatched {
+        pos: [usize; 2],
+        direction: Dir,
+        neighbour: [usize; 2],
+    },
+}
+
+#[derive(Debug)]
+pub struct MazeAnalysis {
+    pub start: [usize; 2],
+    pub start_shape: char,
+    pub issues: Vec<ConnectionIssue>,
+    pub loops: Vec<Vec<[usize; 2]>>,
+}
+
+impl MazeAnalysis {
+    pub fn start_loop(&self) -> Option<&Vec<[usize; 2]>> {
+        self.loops.iter().find(|l| l.contains(&self.start))
+    }
+}
+
+pub fn analyse(sketch: &str) -> Result<MazeAnalysis, MazeError> {
+    let mut sketch = parse(sketch)?;
+    let start = find_start(&sketch)?;
+    let start_shape = match start_shapes(&sketch, start)[..] {
+        [] => return Err(MazeError::NoStartShape(start)),
+        [shape] => shape,
+        ref shapes => {
+            return Err(MazeError::AmbiguousStartShape {
+                pos: start,
+                shapes: shapes.to_vec(),
+            })
+        }
+    };
+    sketch[start[0]][start[1]] = start_shape;
+
+    Ok(MazeAnalysis {
+        start,
+        start_shape,
+        issues: connection_issues(&sketch),
+        loops: closed_loops(&sketch),
+    })
+}
+
+pub fn possible_start_shapes(sketch: &str) -> Result<Vec<char>, MazeError> {
+    let sketch = parse(sketch)?;
+    let start = find_start(&sketch)?;
+    Ok(start_shapes(&sketch, start))
+}
+
+fn parse(sketch: &str) -> Result<Vec<Vec<char>>, MazeError> {
+    let sketch: Vec<Vec<char>> = sketch.lines().map(|l| l.chars().collect()).collect();
+
+    for (i, row) in sketch.iter().enumerate() {
+        for (j, symbol) in row.iter().enumerate() {
+            if !SHAPES.contains(symbol) && *symbol != '.' && *symbol != 'S' {
+                return Err(MazeError::UnrecognisedSymbol {
+                    pos: [i, j],
+                    symbol: *symbol,
+                });
+            }
+        }
+    }
+
+    Ok(sketch)
+}
+
+fn find_start(sketch: &[Vec<char>]) -> Result<[usize; 2], MazeError> {
+    let starts: Vec<[usize; 2]> = sketch
+        .iter()
+        .enumerate()
+        .flat_map(|(i, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, symbol)| **symbol == 'S')
+                .map(move |(j, _)| [i, j])
+        })
+        .collect();
+
+    match starts[..] {
+        [] => Err(MazeError::NoStart),
+        [start] => Ok(start),
+        _ => Err(MazeError::MultipleStarts(starts)),
+    }
+}
+
+fn start_shapes(sketch: &[Vec<char>], start: [usize; 2]) -> Vec<char> {
+    SHAPES
+        .iter()
+        .filter(|shape| {
+            connections(**shape).iter().all(|direction| {
+                neighbour(sketch, start, *direction).is_some_and(|pos| {
+                    connections(sketch[pos[0]][pos[1]]).contains(&opposite(*direction))
+                })
+            })
+        })
+        .copied()
+        .collect()
+}
+
+fn connection_issues(sketch: &[Vec<char>]) -> Vec<ConnectionIssue> {
+    let mut issues = vec![];
+
+    for (i, row) in sketch.iter().enumerate() {
+        for (j, symbol) in row.iter().enumerate() {
+            for direction in connections(*symbol) {
+                match neighbour(sketch, [i, j], direction) {
+                    None => issues.push(ConnectionIssue::Dangling {
+                        pos: [i, j],
+                        direction,
+                    }),
+                    Some(pos) if sketch[pos[0]][pos[1]] == '.' => {
+                        issues.push(ConnectionIssue::Dangling {
+                            pos: [i, j],
+                            direction,
+                        })
+                    }
+                    Some(pos) => {
+                        if !connections(sketch[pos[0]][pos[1]]).contains(&opposite(direction)) {
+                            issues.push(ConnectionIssue::Mismatched {
+                                pos: [i, j],
+                                direction,
+                                neighbour: pos,
+                            })
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+fn closed_loops(sketch: &[Vec<char>]) -> Vec<Vec<[usize; 2]>> {
+    let mut visited = HashSet::new();
+    let mut loops = vec![];
+
+    for (i, row) in sketch.iter().enumerate() {
+        for j in 0..row.len() {
+            if visited.contains(&[i, j]) {
+                continue;
+            }
+
+            let mut path = vec![];
+            let mut pos = [i, j];
+            let mut came_from = None;
+
+            let closed = loop {
+                visited.insert(pos);
+                path.push(pos);
+
+                let next = connections(sketch[pos[0]][pos[1]])
+                    .into_iter()
+                    .filter(|direction| Some(opposite(*direction)) != came_from)
+                    .find_map(|direction| {
+                        connected_neighbour(sketch, pos, direction).map(|next| (direction, next))
+                    });
+
+                let Some((direction, next)) = next else {
+                    break false;
+                };
+
+                if next == [i, j] {
+                    break true;
+                }
+                if visited.contains(&next) {
+                    break false;
+                }
+
+                came_from = Some(direction);
+                pos = next;
+            };
+
+            if closed && path.len() > 2 {
+                loops.push(path);
+            }
+        }
+    }
+
+    loops
+}
+
+fn connections(symbol: char) -> Vec<Dir> {
+    match symbol {
+        '|' => vec![Dir::Up, Dir::Down],
+        '-' => vec![Dir::Left, Dir::Right],
+        'L' => vec![Dir::Up, Dir::Right],
+        'J' => vec![Dir::Up, Dir::Left],
+        '7' => vec![Dir::Down, Dir::Left],
+        'F' => vec![Dir::Down, Dir::Right],
+        _ => vec![],
+    }
+}
+
+fn connected_neighbour(
+    sketch: &[Vec<char>],
+    pos: [usize; 2],
+    direction: Dir,
+) -> Option<[usize; 2]> {
+    neighbour(sketch, pos, direction)
+        .filter(|next| connections(sketch[next[0]][next[1]]).contains(&opposite(direction)))
+}
+
+fn neighbour(sketch: &[Vec<char>], pos: [usize; 2], direction: Dir) -> Option<[usize; 2]> {
+    let [i, j] = pos;
+    let next = match direction {
+        Dir::Up => [i.checked_sub(1)?, j],
+        Dir::Down => [i + 1, j],
+        Dir::Left => [i, j.checked_sub(1)?],
+        Dir::Right => [i, j + 1],
+    };
+    sketch.get(next[0])?.get(next[1])?;
+    Some(next)
+}
+
+fn opposite(direction: Dir) -> Dir {
+    match direction {
+        Dir::Up => Dir::Down,
+        Dir::Down => Dir::Up,
+        Dir::Left => Dir::Right,
+        Dir::Right => Dir::Left,
+    }
+}
+
+#[cfg(test)]
+mod analyse_tests {
+    use super::*;
+
+    #[test]
+    fn resolves_start_and_finds_loop_in_example_input() {
+        let sketch = "-L|F7
+7S-7|
+L|7||
+-L-J|
+L|-JF";
+
+        let analysis = analyse(sketch).unwrap();
+
+        assert_eq!([1, 1], analysis.start);
+        assert_eq!('F', analysis.start_shape);
+        assert_eq!(
+            Some(&vec![
+                [1, 1],
+                [2, 1],
+                [3, 1],
+                [3, 2],
+                [3, 3],
+                [2, 3],
+                [1, 3],
+                [1, 2]
+            ]),
+            analysis.start_loop()
+        );
+    }
+
+    #[test]
+    fn finds_loops_not_passing_through_start() {
+        let sketch = "S7F7
+LJLJ";
+
+        let analysis = analyse(sketch).unwrap();
+
+        assert_eq!(
+            vec![
+                vec![[0, 0], [1, 0], [1, 1], [0, 1]],
+                vec![[0, 2], [1, 2], [1, 3], [0, 3]]
+            ],
+            analysis.loops
+        );
+    }
+
+    #[test]
+    fn reports_dangling_and_mismatched_pipes() {
+        let sketch = "S7.
+LJ|
+-.7";
+
+        let analysis = analyse(sketch).unwrap();
+
+        assert_eq!(
+            vec![
+                ConnectionIssue::Dangling {
+                    pos: [1, 2],
+                    direction: Dir::Up
+                },
+                ConnectionIssue::Mismatched {
+                    pos: [1, 2],
+                    direction: Dir::Down,
+                    neighbour: [2, 2]
+                },
+                ConnectionIssue::Dangling {
+                    pos: [2, 0],
+                    direction: Dir::Left
+                },
+                ConnectionIssue::Dangling {
+                    pos: [2, 0],
+                    direction: Dir::Right
+                },
+                ConnectionIssue::Dangling {
+                    pos: [2, 2],
+                    direction: Dir::Down
+                },
+                ConnectionIssue::Dangling {
+                    pos: [2, 2],
+                    direction: Dir::Left
+                },
+            ],
+            analysis.issues
+        );
+    }
+
+    #[test]
+    fn errors_when_start_shape_is_ambiguous() {
+        let sketch = ".|.
+-S-
+.|.";
+
+        assert_eq!(
+            Err(MazeError::AmbiguousStartShape {
+                pos: [1, 1],
+                shapes: vec!['|', '-', 'L', 'J', '7', 'F']
+            }),
+            analyse(sketch).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn errors_when_start_has_no_shape() {
+        let sketch = "...
+.S-
+...";
+
+        assert_eq!(
+            Err(MazeError::NoStartShape([1, 1])),
+            analyse(sketch).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn errors_on_missing_or_unrecognised_symbols() {
+        assert_eq!(Err(MazeError::NoStart), analyse("F7\nLJ").map(|_| ()));
+        assert_eq!(
+            Err(MazeError::UnrecognisedSymbol {
+                pos: [0, 1],
+                symbol: 'x'
+            }),
+            analyse("Sx").map(|_| ())
+        );
+    }
+}
+
+#[cfg(test)]
+mod possible_start_shapes_tests {
+    use super::*;
+
+    #[test]
+    fn lists_every_consistent_shape() {
+        let sketch = ".|.
+.S-
+.|.";
+
+        assert_eq!(vec!['|', 'L', 'F'], possible_start_shapes(sketch).unwrap());
+    }
+}
@@ -0,0 +1,200 @@
+use super::main_loop;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+
+const TILE_SIZE: usize = 10;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Tile {
+    Loop(char),
+    Farthest(char),
+    Inside,
+    Outside,
+}
+
+pub fn classify(sketch: &str) -> Vec<Vec<Tile>> {
+    let (sketch, path) = main_loop(sketch);
+    classify_loop(&sketch, &path)
+}
+
+fn classify_loop(sketch: &[Vec<char>], path: &[[usize; 2]]) -> Vec<Vec<Tile>> {
+    let loop_positions: HashSet<[usize; 2]> = path.iter().copied().collect();
+    let farthest = path[path.len() / 2];
+
+    sketch
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let mut inside = false;
+            row.iter()
+                .enumerate()
+                .map(|(j, symbol)| {
+                    if loop_positions.contains(&[i, j]) {
+                        if matches!(symbol, '|' | 'F' | '7') {
+                            inside = !inside;
+                        }
+                        if [i, j] == farthest {
+                            Tile::Farthest(*symbol)
+                        } else {
+                            Tile::Loop(*symbol)
+                        }
+                    } else if inside {
+                        Tile::Inside
+                    } else {
+                        Tile::Outside
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub fn render_text(sketch: &str) -> String {
+    classify(sketch)
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|tile| match tile {
+                    Tile::Loop(symbol) => box_drawing(*symbol),
+                    Tile::Farthest(_) => '●',
+                    Tile::Inside => 'I',
+                    Tile::Outside => 'O',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn render_svg(sketch: &str) -> String {
+    let (sketch, path) = main_loop(sketch);
+    let tiles = classify_loop(&sketch, &path);
+    let height = tiles.len() * TILE_SIZE;
+    let width = tiles.first().map_or(0, |row| row.len()) * TILE_SIZE;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+    );
+
+    for (i, row) in tiles.iter().enumerate() {
+        for (j, tile) in row.iter().enumerate() {
+            let fill = match tile {
+                Tile::Inside => "#7fc97f",
+                Tile::Outside => "#eeeeee",
+                Tile::Loop(_) | Tile::Farthest(_) => continue,
+            };
+            svg += &format!(
+                "  <rect x=\"{}\" y=\"{}\" width=\"{TILE_SIZE}\" height=\"{TILE_SIZE}\" fill=\"{fill}\"/>\n",
+                j * TILE_SIZE,
+                i * TILE_SIZE
+            );
+        }
+    }
+
+    let points = path
+        .iter()
+        .map(|pos| {
+            let [x, y] = centre(*pos);
+            format!("{x},{y}")
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    svg += &format!(
+        "  <polygon points=\"{points}\" fill=\"none\" stroke=\"#386cb0\" stroke-width=\"2\"/>\n"
+    );
+
+    let [x, y] = centre(path[path.len() / 2]);
+    svg += &format!(
+        "  <circle cx=\"{x}\" cy=\"{y}\" r=\"{}\" fill=\"#f0027f\"/>\n",
+        TILE_SIZE / 3
+    );
+
+    svg += "</svg>\n";
+    svg
+}
+
+pub fn export_svg(sketch: &str, file_path: &str) -> io::Result<()> {
+    fs::write(file_path, render_svg(sketch))
+}
+
+fn centre(pos: [usize; 2]) -> [usize; 2] {
+    [
+        pos[1] * TILE_SIZE + TILE_SIZE / 2,
+        pos[0] * TILE_SIZE + TILE_SIZE / 2,
+    ]
+}
+
+fn box_drawing(symbol: char) -> char {
+    match symbol {
+        'F' => '┌',
+        '7' => '┐',
+        'L' => '└',
+        'J' => '┘',
+        '-' => '─',
+        '|' => '│',
+        _ => panic!("Unexpected loop symbol: {:?}", symbol),
+    }
+}
+
+#[cfg(test)]
+mod render_text_tests {
+    use super::*;
+
+    #[test]
+    fn draws_loop_with_inside_outside_and_farthest_tiles() {
+        let sketch = "......
+.S--7.
+.|..|.
+.L--J.
+......";
+
+        let expected = "OOOOOO
+O┌──┐O
+O│II│O
+O└──●O
+OOOOOO";
+
+        assert_eq!(expected, render_text(sketch));
+    }
+
+    #[test]
+    fn non_loop_pipes_are_classified() {
+        let sketch = "-L|F7
+7S-7|
+L|7||
+-L-J|
+L|-JF";
+
+        let expected = "OOOOO
+O┌─┐O
+O│I│O
+O└─●O
+OOOOO";
+
+        assert_eq!(expected, render_text(sketch));
+    }
+}
+
+#[cfg(test)]
+mod render_svg_tests {
+    use super::*;
+
+    #[test]
+    fn draws_inside_tiles_loop_and_farthest_point() {
+        let sketch = ".....
+.S-7.
+.|.|.
+.L-J.
+.....";
+
+        let svg = render_svg(sketch);
+
+        assert!(svg.starts_with("<svg"));
+        assert!(
+            svg.contains("<rect x=\"20\" y=\"20\" width=\"10\" height=\"10\" fill=\"#7fc97f\"/>")
+        );
+        assert!(svg.contains("<polygon points=\"15,15 15,25 15,35 25,35 35,35 35,25 35,15 25,15\""));
+        assert!(svg.contains("<circle cx=\"35\" cy=\"35\""));
+    }
+}
@@ -6,77 +6,144 @@ pub fn run() {
     println!("pt2: {}", pt2(&universe, 1_000_000));
 }
 
-fn pt2(universe: &str, multiplier: usize) -> usize {
-    let mut vertical_multipliers: Vec<usize> = vec![];
+fn pt2(universe: &str, multiplier: u64) -> u128 {
+    total_distance(universe, multiplier, multiplier).unwrap()
+}
 
-    for line in universe.lines() {
-        if line.chars().all(|point| point == '.') {
-            vertical_multipliers.push(multiplier);
-        } else {
-            vertical_multipliers.push(1);
-        }
-    }
+fn pt1(universe: &str) -> u128 {
+    total_distance(universe, 2, 2).unwrap()
+}
 
-    let transposed_universe = transpose_universe(&universe);
+// An expansion factor is how many lines each empty line becomes, so it has to
+// be at least 1
+#[derive(Debug, PartialEq)]
+pub enum ExpansionError {
+    ZeroFactor,
+}
 
-    let mut horizontal_multipliers: Vec<usize> = vec![];
+pub fn total_distance(
+    universe: &str,
+    row_expansion: u64,
+    col_expansion: u64,
+) -> Result<u128, ExpansionError> {
+    let positions = galaxy_positions(universe);
 
-    for line in transposed_universe.lines() {
-        if line.chars().all(|point| point == '.') {
-            horizontal_multipliers.push(multiplier);
-        } else {
-            horizontal_multipliers.push(1);
-        }
-    }
+    let rows: Vec<_> = positions.iter().map(|pos| pos[0]).collect();
+    let cols: Vec<_> = positions.iter().map(|pos| pos[1]).collect();
 
-    let positions = galaxy_positions(universe);
+    let mut rows = expanded_coordinates(&rows, row_expansion)?;
+    let mut cols = expanded_coordinates(&cols, col_expansion)?;
+    rows.sort_unstable();
+    cols.sort_unstable();
 
-    let pairs = galaxy_pairs(positions);
+    Ok(sum_of_pairwise_distances(&rows) + sum_of_pairwise_distances(&cols))
+}
 
-    let mut sum_of_path_lengths = 0;
+pub struct ExpandedUniverse {
+    galaxies: Vec<[u128; 2]>,
+}
 
-    for [pos_1, pos_2] in pairs {
-        let mut path_length = 0;
+impl ExpandedUniverse {
+    pub fn new(universe: &str, expansion: u64) -> Result<ExpandedUniverse, ExpansionError> {
+        let positions = galaxy_positions(universe);
 
-        let start_horizontal = pos_1[1].min(pos_2[1]);
-        let end_horizontal = pos_1[1].max(pos_2[1]);
+        let rows: Vec<_> = positions.iter().map(|pos| pos[0]).collect();
+        let cols: Vec<_> = positions.iter().map(|pos| pos[1]).collect();
 
-        for n in start_horizontal..end_horizontal {
-            let multiplier = horizontal_multipliers[n];
-            path_length += multiplier;
-        }
+        let rows = expanded_coordinates(&rows, expansion)?;
+        let cols = expanded_coordinates(&cols, expansion)?;
 
-        let start_vertical = pos_1[0].min(pos_2[0]);
-        let end_vertical = pos_1[0].max(pos_2[0]);
+        Ok(ExpandedUniverse {
+            galaxies: rows.into_iter().zip(cols).map(|(i, j)| [i, j]).collect(),
+        })
+    }
 
-        for n in start_vertical..end_vertical {
-            let multiplier = vertical_multipliers[n];
-            path_length += multiplier;
-        }
+    // Galaxies are indexed from 0 in the order they appear reading the image
+    pub fn galaxies(&self) -> &[[u128; 2]] {
+        &self.galaxies
+    }
 
-        sum_of_path_lengths += path_length;
+    pub fn distance(&self, galaxy_a: usize, galaxy_b: usize) -> u128 {
+        let [i_a, j_a] = self.galaxies[galaxy_a];
+        let [i_b, j_b] = self.galaxies[galaxy_b];
+        i_a.abs_diff(i_b) + j_a.abs_diff(j_b)
     }
 
-    sum_of_path_lengths
-}
+    pub fn nearest(&self, galaxy: usize) -> Option<(usize, u128)> {
+        self.others(galaxy).min_by_key(|(_, distance)| *distance)
+    }
 
-fn pt1(universe: &str) -> usize {
-    let transposed_universe = transpose_universe(&universe);
+    pub fn farthest(&self, galaxy: usize) -> Option<(usize, u128)> {
+        self.others(galaxy)
+            .rev()
+            .max_by_key(|(_, distance)| *distance)
+    }
 
-    let expanded_universe = expand_universe(&transposed_universe);
+    pub fn distance_matrix(&self) -> Vec<Vec<u128>> {
+        (0..self.galaxies.len())
+            .map(|a| {
+                (0..self.galaxies.len())
+                    .map(|b| self.distance(a, b))
+                    .collect()
+            })
+            .collect()
+    }
 
-    // this is stupid but I went with the approach because I wanted to figure out how to transpose a Vec<Vec<_>>
-    let expanded_universe = transpose_universe(&expanded_universe);
-    let expanded_universe = transpose_universe(&expanded_universe);
-    let expanded_universe = transpose_universe(&expanded_universe);
+    pub fn distance_matrix_csv(&self) -> String {
+        self.distance_matrix()
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|distance| distance.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 
-    let expanded_universe = expand_universe(&expanded_universe);
+    fn others(&self, galaxy: usize) -> impl DoubleEndedIterator<Item = (usize, u128)> + '_ {
+        (0..self.galaxies.len())
+            .filter(move |other| *other != galaxy)
+            .map(move |other| (other, self.distance(galaxy, other)))
+    }
+}
 
-    let lengths = shortest_path_lengths(&expanded_universe);
+fn expanded_coordinates(
+    coordinates: &[usize],
+    expansion: u64,
+) -> Result<Vec<u128>, ExpansionError> {
+    if expansion == 0 {
+        return Err(ExpansionError::ZeroFactor);
+    }
 
-    lengths.iter().sum()
+    let mut occupied = coordinates.to_vec();
+    occupied.sort_unstable();
+    occupied.dedup();
+
+    Ok(coordinates
+        .iter()
+        .map(|coordinate| {
+            let empty_lines_before = coordinate - occupied.partition_point(|o| o < coordinate);
+            *coordinate as u128 + empty_lines_before as u128 * (expansion as u128 - 1)
+        })
+        .collect())
 }
 
+// Expects coordinates sorted in ascending order
+fn sum_of_pairwise_distances(coordinates: &[u128]) -> u128 {
+    let mut prefix_sum = 0;
+    let mut total = 0;
+
+    for (k, coordinate) in coordinates.iter().enumerate() {
+        total += coordinate * k as u128 - prefix_sum;
+        prefix_sum += coordinate;
+    }
+
+    total
+}
+
+#[cfg(test)]
 fn transpose_universe(universe: &str) -> String {
     let universe: Vec<_> = universe
         .lines()
@@ -103,6 +170,7 @@ fn transpose_universe(universe: &str) -> String {
     transposed.join("\n")
 }
 
+#[cfg(test)]
 fn expand_universe(universe: &str) -> String {
     let mut expanded_universe = vec![];
 
@@ -120,6 +188,7 @@ fn expand_universe(universe: &str) -> String {
     expanded_universe.join("\n")
 }
 
+#[cfg(test)]
 fn galaxy_pairs(mut galaxy_positions: Vec<[usize; 2]>) -> Vec<[[usize; 2]; 2]> {
     let mut pairs: Vec<[[usize; 2]; 2]> = vec![];
     while let Some(pos_a) = galaxy_positions.pop() {
@@ -130,6 +199,7 @@ fn galaxy_pairs(mut galaxy_positions: Vec<[usize; 2]>) -> Vec<[[usize; 2]; 2]> {
     pairs
 }
 
+#[cfg(test)]
 fn shortest_path_lengths(universe: &str) -> Vec<usize> {
     let positions = galaxy_positions(universe);
     let pairs = galaxy_pairs(positions);
@@ -248,6 +318,115 @@ mod pt2_tests {
     }
 }
 
+#[cfg(test)]
+mod total_distance_tests {
+    use super::*;
+
+    const EXAMPLE: &str = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+
+    #[test]
+    fn independent_row_and_column_expansion() {
+        let universe = "#..
+...
+..#";
+
+        assert_eq!(2 + 2, total_distance(universe, 1, 1).unwrap());
+        assert_eq!(3 + 2, total_distance(universe, 2, 1).unwrap());
+        assert_eq!(2 + 11, total_distance(universe, 1, 10).unwrap());
+    }
+
+    #[test]
+    fn matches_shortest_path_lengths_of_expanded_universe() {
+        let expanded_universe = expand_universe(&transpose_universe(EXAMPLE));
+        let expanded_universe = transpose_universe(&expanded_universe);
+        let expanded_universe = transpose_universe(&expanded_universe);
+        let expanded_universe = transpose_universe(&expanded_universe);
+        let expanded_universe = expand_universe(&expanded_universe);
+
+        let expected: usize = shortest_path_lengths(&expanded_universe).iter().sum();
+
+        assert_eq!(expected as u128, total_distance(EXAMPLE, 2, 2).unwrap());
+    }
+
+    #[test]
+    fn does_not_overflow_with_huge_expansion() {
+        let universe = "#.#";
+
+        assert_eq!(
+            u64::MAX as u128 + 1,
+            total_distance(universe, 1, u64::MAX).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_zero_expansion() {
+        assert_eq!(Err(ExpansionError::ZeroFactor), total_distance("#.#", 0, 1));
+        assert!(ExpandedUniverse::new("#.#", 0).is_err());
+    }
+}
+
+#[cfg(test)]
+mod expanded_universe_tests {
+    use super::*;
+
+    const EXAMPLE: &str = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+
+    #[test]
+    fn distances_between_galaxies_from_example_input() {
+        let universe = ExpandedUniverse::new(EXAMPLE, 2).unwrap();
+
+        assert_eq!(9, universe.distance(4, 8));
+        assert_eq!(15, universe.distance(0, 6));
+        assert_eq!(17, universe.distance(2, 5));
+        assert_eq!(5, universe.distance(7, 8));
+    }
+
+    #[test]
+    fn nearest_and_farthest_galaxies() {
+        let universe = ExpandedUniverse::new(EXAMPLE, 2).unwrap();
+
+        assert_eq!(Some((8, 5)), universe.nearest(7));
+        assert_eq!(Some((2, 17)), universe.farthest(6));
+        assert_eq!(Some((5, 15)), universe.farthest(0));
+        assert_eq!(None, ExpandedUniverse::new("#..", 2).unwrap().nearest(0));
+    }
+
+    #[test]
+    fn distance_matrix_at_arbitrary_expansion() {
+        let universe = ExpandedUniverse::new("#..\n...\n..#", 100).unwrap();
+
+        assert_eq!(vec![[0, 0], [101, 101]], universe.galaxies());
+        assert_eq!(vec![vec![0, 202], vec![202, 0]], universe.distance_matrix());
+        assert_eq!("0,202\n202,0", universe.distance_matrix_csv());
+    }
+
+    #[test]
+    fn matrix_sums_to_total_distance() {
+        let universe = ExpandedUniverse::new(EXAMPLE, 10).unwrap();
+        let sum: u128 = universe.distance_matrix().iter().flatten().sum();
+
+        assert_eq!(total_distance(EXAMPLE, 10, 10).unwrap() * 2, sum);
+    }
+}
+
 #[cfg(test)]
 mod galaxy_positions_tests {
     use super::*;
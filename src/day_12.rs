@@ -1,3 +1,5 @@
+use rand::Rng;
+#[cfg(test)]
 use std::collections::HashMap;
 use std::fs;
 
@@ -7,6 +9,7 @@ pub fn run() {
     println!("pt2: {}", pt2(&contents));
 }
 
+#[cfg(test)]
 fn valid(pattern: &Vec<usize>, pattern_index: usize, broken_count: usize) -> bool {
     if pattern_index == pattern.len() && broken_count == 0 {
         return true;
@@ -19,6 +22,7 @@ fn valid(pattern: &Vec<usize>, pattern_index: usize, broken_count: usize) -> boo
     false
 }
 
+#[cfg(test)]
 fn score(
     springs: &Vec<char>,
     pattern: &Vec<usize>,
@@ -63,46 +67,251 @@ fn score(
     answer
 }
 
-fn pt1(contents: &str) -> usize {
-    let mut answer = 0;
+fn pt1(contents: &str) -> u128 {
+    total_arrangements(contents, 1, '?').unwrap()
+}
 
-    for line in contents.lines() {
-        let (springs, pattern) = line.split_once(' ').unwrap();
-        let springs: Vec<_> = springs.chars().collect();
-        let pattern = pattern
-            .split(',')
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect();
+fn pt2(contents: &str) -> u128 {
+    total_arrangements(contents, 5, '?').unwrap()
+}
 
-        let mut memo = HashMap::new();
+#[derive(Debug, PartialEq)]
+pub enum ArrangementError {
+    ZeroFactor,
+    Overflow,
+}
 
-        answer += score(&springs, &pattern, [0, 0, 0], &mut memo);
+pub fn total_arrangements(
+    contents: &str,
+    unfold_factor: usize,
+    separator: char,
+) -> Result<u128, ArrangementError> {
+    let mut counter = ArrangementCounter::default();
+
+    contents.lines().try_fold(0u128, |total, line| {
+        let (springs, pattern) = unfold(line, unfold_factor, separator)?;
+        counter
+            .count(&springs, &pattern)
+            .and_then(|count| total.checked_add(count))
+            .ok_or(ArrangementError::Overflow)
+    })
+}
+
+pub fn unfold(
+    line: &str,
+    factor: usize,
+    separator: char,
+) -> Result<(Vec<char>, Vec<usize>), ArrangementError> {
+    if factor == 0 {
+        return Err(ArrangementError::ZeroFactor);
     }
 
-    answer
+    let (springs, pattern) = line.split_once(' ').unwrap();
+
+    let springs = vec![springs; factor].join(&separator.to_string());
+    let springs: Vec<_> = springs.chars().collect();
+
+    let pattern = vec![pattern; factor].join(",");
+    let pattern = pattern
+        .split(',')
+        .map(|s| s.parse::<usize>().unwrap())
+        .collect();
+
+    Ok((springs, pattern))
 }
 
-fn pt2(contents: &str) -> usize {
-    let mut answer = 0;
+// Reuses the same table for every row it counts, only growing it when a
+// longer row comes along.
+#[derive(Default)]
+pub struct ArrangementCounter {
+    table: Vec<u128>,
+    width: usize,
+    damaged_runs: Vec<usize>,
+}
 
-    for line in contents.lines() {
-        let (springs, pattern) = line.split_once(' ').unwrap();
+impl ArrangementCounter {
+    // None if the count doesn't fit in a u128
+    pub fn count(&mut self, springs: &[char], pattern: &[usize]) -> Option<u128> {
+        let n = springs.len();
+        let width = pattern.len() + 1;
+        self.width = width;
 
-        let springs = [springs; 5].join("?");
-        let springs: Vec<_> = springs.chars().collect();
+        // damaged_runs[i] is how many springs from i onwards could be damaged
+        self.damaged_runs.clear();
+        self.damaged_runs.resize(n + 1, 0);
+        for i in (0..n).rev() {
+            if springs[i] != '.' {
+                self.damaged_runs[i] = self.damaged_runs[i + 1] + 1;
+            }
+        }
 
-        let pattern = [pattern; 5].join(",");
-        let pattern = pattern
-            .split(',')
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect();
+        // table[i * width + j] is the number of arrangements of springs[i..]
+        // matching pattern[j..], with an extra row for a group ending the row
+        self.table.clear();
+        self.table.resize((n + 2) * width, 0);
+        self.table[n * width + pattern.len()] = 1;
+        self.table[(n + 1) * width + pattern.len()] = 1;
 
-        let mut memo = HashMap::new();
+        for i in (0..n).rev() {
+            for j in 0..width {
+                let mut ways = 0;
+
+                if springs[i] != '#' {
+                    ways = self.table[(i + 1) * width + j];
+                }
+
+                if springs[i] != '.' && j < pattern.len() {
+                    let group = pattern[j];
+                    if self.damaged_runs[i] >= group
+                        && (i + group == n || springs[i + group] != '#')
+                    {
+                        ways = ways.checked_add(self.table[(i + group + 1) * width + j + 1])?;
+                    }
+                }
+
+                self.table[i * width + j] = ways;
+            }
+        }
 
-        answer += score(&springs, &pattern, [0, 0, 0], &mut memo);
+        Some(self.table[0])
     }
 
-    answer
+    // The arrangements of springs[spring_index..] matching
+    // pattern[pattern_index..] for the row counted last
+    fn ways(&self, spring_index: usize, pattern_index: usize) -> u128 {
+        self.table[spring_index * self.width + pattern_index]
+    }
+}
+
+pub struct SpringRow {
+    springs: Vec<char>,
+    pattern: Vec<usize>,
+    counter: ArrangementCounter,
+}
+
+// Walks the counter's table, where each step either leaves one spring
+// operational or places the next whole group of damaged springs
+impl SpringRow {
+    // None if the number of arrangements doesn't fit in a u128
+    pub fn parse(line: &str) -> Option<SpringRow> {
+        let (springs, pattern) = unfold(line, 1, '?').unwrap();
+
+        let mut counter = ArrangementCounter::default();
+        counter.count(&springs, &pattern)?;
+
+        Some(SpringRow {
+            springs,
+            pattern,
+            counter,
+        })
+    }
+
+    pub fn count(&self) -> u128 {
+        self.counter.ways(0, 0)
+    }
+
+    // Arrangements are yielded in lexicographic order, so '#' comes before '.'
+    pub fn arrangements(&self) -> Arrangements<'_> {
+        let stack = if self.count() > 0 {
+            vec![((0, 0), String::new())]
+        } else {
+            vec![]
+        };
+
+        Arrangements { row: self, stack }
+    }
+
+    pub fn nth_arrangement(&self, mut n: u128) -> Option<String> {
+        if n >= self.count() {
+            return None;
+        }
+
+        let mut position = (0, 0);
+        let mut arrangement = String::new();
+
+        while position.0 < self.springs.len() {
+            for (springs, next_position) in self.branches(position) {
+                let count = self.counter.ways(next_position.0, next_position.1);
+                if n < count {
+                    arrangement += &springs;
+                    position = next_position;
+                    break;
+                }
+                n -= count;
+            }
+        }
+
+        Some(arrangement)
+    }
+
+    pub fn random_arrangement<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<String> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        self.nth_arrangement(rng.gen_range(0..count))
+    }
+
+    // Mirrors the transitions in `ArrangementCounter::count`, ordered with '#'
+    // first. A group is followed by an operational spring unless it ends the
+    // row, which takes the position past the end onto the table's extra row.
+    fn branches(
+        &self,
+        (spring_index, pattern_index): (usize, usize),
+    ) -> Vec<(String, (usize, usize))> {
+        let n = self.springs.len();
+        let mut branches = vec![];
+
+        if self.springs[spring_index] != '.' && pattern_index < self.pattern.len() {
+            let group = self.pattern[pattern_index];
+            let end = spring_index + group;
+            let fits = end <= n
+                && self.springs[spring_index..end]
+                    .iter()
+                    .all(|spring| *spring != '.')
+                && (end == n || self.springs[end] != '#');
+
+            if fits {
+                let mut springs = "#".repeat(group);
+                if end < n {
+                    springs.push('.');
+                }
+                branches.push((springs, (end + 1, pattern_index + 1)));
+            }
+        }
+
+        if self.springs[spring_index] != '#' {
+            branches.push((".".to_string(), (spring_index + 1, pattern_index)));
+        }
+
+        branches
+    }
+}
+
+pub struct Arrangements<'a> {
+    row: &'a SpringRow,
+    stack: Vec<((usize, usize), String)>,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((position, arrangement)) = self.stack.pop() {
+            if position.0 >= self.row.springs.len() {
+                return Some(arrangement);
+            }
+
+            for (springs, next_position) in self.row.branches(position).into_iter().rev() {
+                if self.row.counter.ways(next_position.0, next_position.1) > 0 {
+                    self.stack
+                        .push((next_position, arrangement.clone() + &springs));
+                }
+            }
+        }
+
+        None
+    }
 }
 
 #[cfg(test)]
@@ -138,3 +347,122 @@ mod day_12_pt_2_tests {
         assert_eq!(525152, pt2(example_input));
     }
 }
+
+#[cfg(test)]
+mod arrangement_counter_tests {
+    use super::*;
+
+    #[test]
+    fn matches_memoised_score() {
+        let example_input = "???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1";
+
+        let mut counter = ArrangementCounter::default();
+
+        for line in example_input.lines() {
+            for factor in 1..=3 {
+                let (springs, pattern) = unfold(line, factor, '?').unwrap();
+                let mut memo = HashMap::new();
+                let expected = score(&springs, &pattern, [0, 0, 0], &mut memo);
+                assert_eq!(Some(expected as u128), counter.count(&springs, &pattern));
+            }
+        }
+    }
+
+    #[test]
+    fn unfolds_with_configurable_factor_and_separator() {
+        assert_eq!(Ok((vec!['#', '.', '#'], vec![1, 1])), unfold("# 1", 2, '.'));
+        assert_eq!(Ok(1), total_arrangements("# 1", 2, '.'));
+        assert_eq!(Ok(0), total_arrangements("# 1", 2, '#'));
+        assert_eq!(Ok(1), total_arrangements("# 1", 2, '?'));
+    }
+
+    #[test]
+    fn rejects_zero_unfold_factor() {
+        assert_eq!(Err(ArrangementError::ZeroFactor), unfold("# 1", 0, '?'));
+        assert_eq!(
+            Err(ArrangementError::ZeroFactor),
+            total_arrangements("???.### 1,1,3", 0, '?')
+        );
+    }
+
+    #[test]
+    fn counts_beyond_usize_with_large_unfold_factor() {
+        let count = total_arrangements("?###???????? 3,2,1", 20, '?').unwrap();
+        assert!(count > u64::MAX as u128);
+    }
+
+    #[test]
+    fn reports_overflowing_counts() {
+        assert_eq!(
+            Err(ArrangementError::Overflow),
+            total_arrangements("?? 1", 400, '?')
+        );
+
+        let line = format!("{} {}", "?".repeat(400), vec!["1"; 100].join(","));
+        assert!(SpringRow::parse(&line).is_none());
+    }
+}
+
+#[cfg(test)]
+mod spring_row_tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn enumerates_arrangements_in_lexicographic_order() {
+        let row = SpringRow::parse("?###???????? 3,2,1").unwrap();
+
+        let arrangements: Vec<_> = row.arrangements().collect();
+
+        assert_eq!(10, arrangements.len());
+        assert_eq!(".###.##.#...", arrangements[0]);
+        assert_eq!(".###....##.#", arrangements[9]);
+        assert!(arrangements.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn enumerates_nothing_for_impossible_row() {
+        let row = SpringRow::parse("#.# 3").unwrap();
+
+        assert_eq!(0, row.count());
+        assert_eq!(None, row.arrangements().next());
+        assert_eq!(None, row.nth_arrangement(0));
+    }
+
+    #[test]
+    fn nth_arrangement_matches_enumeration() {
+        let row = SpringRow::parse(".??..??...?##. 1,1,3").unwrap();
+
+        let arrangements: Vec<_> = row.arrangements().collect();
+
+        assert_eq!(4, row.count());
+        for (n, arrangement) in arrangements.iter().enumerate() {
+            assert_eq!(Some(arrangement.clone()), row.nth_arrangement(n as u128));
+        }
+        assert_eq!(None, row.nth_arrangement(4));
+    }
+
+    #[test]
+    fn random_arrangement_is_a_valid_arrangement() {
+        let row = SpringRow::parse("?###???????? 3,2,1").unwrap();
+        let arrangements: Vec<_> = row.arrangements().collect();
+        assert_eq!(10, arrangements.len());
+
+        let samples: HashSet<_> = (0..20)
+            .map(|seed| {
+                let mut rng = StdRng::seed_from_u64(seed);
+                row.random_arrangement(&mut rng).unwrap()
+            })
+            .collect();
+
+        assert!(samples.iter().all(|sample| arrangements.contains(sample)));
+        assert!(samples.len() > 1);
+    }
+}
@@ -1,5 +1,7 @@
 use std::fs;
 
+pub mod symmetry;
+
 pub fn run() {
     let contents = fs::read_to_string("13.txt").unwrap();
     println!("pt1: {}", pt1(&contents));
@@ -16,60 +18,85 @@ fn points_of_reflection(pattern: &Vec<Vec<char>>) -> (Option<usize>, Option<usiz
     (horizontal_point_of_reflection, vertical_point_of_reflection)
 }
 
-fn points_of_reflection_with_smudge(pattern: &Vec<Vec<char>>) -> Vec<usize> {
-    let mut points_of_reflection = vec![];
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
 
-    let lookup = row_reflection_key_lookup(&pattern);
+#[derive(Debug, PartialEq)]
+pub struct Reflection {
+    pub axis: Axis,
+    pub index: usize,
+    pub smudges: Vec<[[usize; 2]; 2]>,
+}
 
-    for row_index in 0..lookup.len() - 1 {
-        let mut i0 = row_index;
-        let mut i1 = row_index + 1;
+impl Reflection {
+    pub fn summary(&self) -> usize {
+        match self.axis {
+            Axis::Horizontal => 100 * (self.index + 1),
+            Axis::Vertical => self.index + 1,
+        }
+    }
+}
 
-        let mut smudge_seen = false;
+pub fn reflections(pattern: &Vec<Vec<char>>, smudge_count: usize) -> Vec<Reflection> {
+    let mut reflections = vec![];
 
-        loop {
-            let key0 = &lookup[i0];
-            let key1 = &lookup[i1];
+    for (index, smudges) in row_reflections(pattern, smudge_count) {
+        reflections.push(Reflection {
+            axis: Axis::Horizontal,
+            index,
+            smudges,
+        });
+    }
 
-            let mut difference_count = reflection_key_difference_count(key0, key1);
+    let transposed_pattern = transpose_pattern(pattern);
+
+    for (index, smudges) in row_reflections(&transposed_pattern, smudge_count) {
+        let smudges = smudges
+            .into_iter()
+            .map(|[[i0, j0], [i1, j1]]| [[j0, i0], [j1, i1]])
+            .collect();
+        reflections.push(Reflection {
+            axis: Axis::Vertical,
+            index,
+            smudges,
+        });
+    }
 
-            if !smudge_seen && difference_count == 1 {
-                smudge_seen = true;
-                difference_count = 0;
-            }
+    reflections
+}
 
-            if difference_count == 0 {
-                if i0 == 0 || i1 == lookup.len() - 1 {
-                    points_of_reflection.push(row_index);
-                    break;
-                } else {
-                    i0 -= 1;
-                    i1 += 1;
+fn row_reflections(
+    pattern: &[Vec<char>],
+    smudge_count: usize,
+) -> Vec<(usize, Vec<[[usize; 2]; 2]>)> {
+    let mut reflections = vec![];
+
+    for row_index in 0..pattern.len().saturating_sub(1) {
+        let mut smudges = vec![];
+
+        let pairs = (0..=row_index).rev().zip(row_index + 1..pattern.len());
+
+        'pairs: for (i0, i1) in pairs {
+            let columns = pattern[i0].iter().zip(&pattern[i1]).enumerate();
+            for (j, (c0, c1)) in columns {
+                if c0 != c1 {
+                    smudges.push([[i0, j], [i1, j]]);
+                    if smudges.len() > smudge_count {
+                        break 'pairs;
+                    }
                 }
-            } else {
-                break;
             }
         }
-    }
-
-    points_of_reflection
-}
-
-fn reflection_key_difference_count(reflection_key_1: &String, reflection_key_2: &String) -> usize {
-    let mut count = 0;
 
-    let reflection_key_1: Vec<_> = reflection_key_1.chars().collect();
-    let reflection_key_2: Vec<_> = reflection_key_2.chars().collect();
-
-    let length = reflection_key_1.len();
-
-    for i in 0..length {
-        if reflection_key_1[i] != reflection_key_2[i] {
-            count += 1;
+        if smudges.len() == smudge_count {
+            reflections.push((row_index, smudges));
         }
     }
 
-    count
+    reflections
 }
 
 fn pt1(contents: &str) -> usize {
@@ -94,41 +121,11 @@ fn pt1(contents: &str) -> usize {
 }
 
 fn pt2(contents: &str) -> usize {
-    let mut answer = 0;
-
-    let patterns = parse_patterns(contents);
-
-    for pattern in patterns {
-        let (horizontal_point, vertical_point) = points_of_reflection(&pattern);
-
-        let horizontal_points_of_reflection = points_of_reflection_with_smudge(&pattern);
-
-        for point in horizontal_points_of_reflection {
-            if let Some(non_smudge_point) = horizontal_point {
-                if point == non_smudge_point {
-                    continue;
-                }
-            }
-
-            answer += 100 * (point + 1);
-        }
-
-        let transposed_pattern = transpose_pattern(&pattern);
-
-        let vertical_points_of_reflection = points_of_reflection_with_smudge(&transposed_pattern);
-
-        for point in vertical_points_of_reflection {
-            if let Some(non_smudge_point) = vertical_point {
-                if point == non_smudge_point {
-                    continue;
-                }
-            }
-
-            answer += point + 1;
-        }
-    }
-
-    answer
+    parse_patterns(contents)
+        .iter()
+        .flat_map(|pattern| reflections(pattern, 1))
+        .map(|reflection| reflection.summary())
+        .sum()
 }
 
 fn parse_patterns(contents: &str) -> Vec<Vec<Vec<char>>> {
@@ -289,7 +286,7 @@ mod day_13_pt2_tests {
     use super::*;
 
     #[test]
-    fn test_points_of_reflection_with_smudge() {
+    fn test_reflections_with_one_smudge() {
         let pattern = vec![
             vec!['#', '.', '#', '#', '.', '.', '#', '#', '.'],
             vec!['.', '.', '#', '.', '#', '#', '.', '#', '.'],
@@ -300,11 +297,13 @@ mod day_13_pt2_tests {
             vec!['#', '.', '#', '.', '#', '#', '.', '#', '.'],
         ];
 
-        assert_eq!(vec![2], points_of_reflection_with_smudge(&pattern));
-
-        let pattern = transpose_pattern(&pattern);
+        let expected = vec![Reflection {
+            axis: Axis::Horizontal,
+            index: 2,
+            smudges: vec![[[0, 0], [5, 0]]],
+        }];
 
-        assert_eq!(vec![4], points_of_reflection_with_smudge(&pattern));
+        assert_eq!(expected, reflections(&pattern, 1));
 
         let pattern = vec![
             vec!['#', '.', '.', '.', '#', '#', '.', '.', '#'],
@@ -316,11 +315,62 @@ mod day_13_pt2_tests {
             vec!['#', '.', '.', '.', '.', '#', '.', '.', '#'],
         ];
 
-        assert_eq!(vec![0, 3], points_of_reflection_with_smudge(&pattern));
+        let expected = vec![Reflection {
+            axis: Axis::Horizontal,
+            index: 0,
+            smudges: vec![[[0, 4], [1, 4]]],
+        }];
+
+        assert_eq!(expected, reflections(&pattern, 1));
+    }
+
+    #[test]
+    fn test_reflections_without_smudges_match_pt1() {
+        let pattern = vec![
+            vec!['#', '.', '#', '#', '.', '.', '#', '#', '.'],
+            vec!['.', '.', '#', '.', '#', '#', '.', '#', '.'],
+            vec!['#', '#', '.', '.', '.', '.', '.', '.', '#'],
+            vec!['#', '#', '.', '.', '.', '.', '.', '.', '#'],
+            vec!['.', '.', '#', '.', '#', '#', '.', '#', '.'],
+            vec!['.', '.', '#', '#', '.', '.', '#', '#', '.'],
+            vec!['#', '.', '#', '.', '#', '#', '.', '#', '.'],
+        ];
+
+        let reflections = reflections(&pattern, 0);
 
-        let pattern = transpose_pattern(&pattern);
+        assert_eq!(1, reflections.len());
+        assert_eq!(Axis::Vertical, reflections[0].axis);
+        assert_eq!(4, reflections[0].index);
+        assert_eq!(5, reflections[0].summary());
+    }
 
-        assert_eq!(0, points_of_reflection_with_smudge(&pattern).len());
+    #[test]
+    fn test_reflections_with_several_smudges() {
+        let pattern = vec![vec!['#', '#', '.', '.'], vec!['.', '#', '.', '#']];
+
+        assert_eq!(
+            vec![Reflection {
+                axis: Axis::Horizontal,
+                index: 0,
+                smudges: vec![[[0, 0], [1, 0]], [[0, 3], [1, 3]]],
+            }],
+            reflections(&pattern, 2)
+        );
+        assert_eq!(
+            vec![
+                Reflection {
+                    axis: Axis::Vertical,
+                    index: 0,
+                    smudges: vec![[[1, 0], [1, 1]]],
+                },
+                Reflection {
+                    axis: Axis::Vertical,
+                    index: 2,
+                    smudges: vec![[[1, 2], [1, 3]]],
+                },
+            ],
+            reflections(&pattern, 1)
+        );
     }
 
     #[test]
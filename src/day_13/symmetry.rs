@@ -0,0 +1,167 @@
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Symmetry {
+    Rotational,
+    MainDiagonal,
+    AntiDiagonal,
+}
+
+// Inclusive row and column bounds of a region within a pattern
+#[derive(Debug, PartialEq)]
+pub struct BoundingBox {
+    pub top: usize,
+    pub left: usize,
+    pub bottom: usize,
+    pub right: usize,
+}
+
+pub fn has_symmetry(pattern: &[Vec<char>], symmetry: Symmetry) -> bool {
+    let height = pattern.len();
+    let width = pattern.first().map_or(0, |row| row.len());
+
+    if symmetry != Symmetry::Rotational && height != width {
+        return false;
+    }
+
+    is_symmetric(pattern, symmetry, [0, 0], [height, width])
+}
+
+pub fn largest_symmetric_regions(pattern: &[Vec<char>], symmetry: Symmetry) -> Vec<BoundingBox> {
+    let height = pattern.len();
+    let width = pattern.first().map_or(0, |row| row.len());
+
+    let mut sizes = vec![];
+    for h in 1..=height {
+        for w in 1..=width {
+            let square = h == w;
+            if h * w > 1 && (symmetry == Symmetry::Rotational || square) {
+                sizes.push([h, w]);
+            }
+        }
+    }
+    sizes.sort_by_key(|[h, w]| std::cmp::Reverse(h * w));
+
+    let mut regions = vec![];
+    let mut region_area = 0;
+
+    for [h, w] in sizes {
+        if !regions.is_empty() && h * w < region_area {
+            break;
+        }
+
+        for top in 0..=height - h {
+            for left in 0..=width - w {
+                if is_symmetric(pattern, symmetry, [top, left], [h, w]) {
+                    region_area = h * w;
+                    regions.push(BoundingBox {
+                        top,
+                        left,
+                        bottom: top + h - 1,
+                        right: left + w - 1,
+                    });
+                }
+            }
+        }
+    }
+
+    regions.sort_by_key(|region| [region.top, region.left]);
+    regions
+}
+
+fn is_symmetric(
+    pattern: &[Vec<char>],
+    symmetry: Symmetry,
+    [top, left]: [usize; 2],
+    [height, width]: [usize; 2],
+) -> bool {
+    (0..height).all(|i| {
+        (0..width).all(|j| {
+            let [mirror_i, mirror_j] = match symmetry {
+                Symmetry::Rotational => [height - 1 - i, width - 1 - j],
+                Symmetry::MainDiagonal => [j, i],
+                Symmetry::AntiDiagonal => [width - 1 - j, height - 1 - i],
+            };
+            pattern[top + i][left + j] == pattern[top + mirror_i][left + mirror_j]
+        })
+    })
+}
+
+#[cfg(test)]
+mod has_symmetry_tests {
+    use super::*;
+
+    fn pattern(rows: &str) -> Vec<Vec<char>> {
+        rows.lines().map(|line| line.chars().collect()).collect()
+    }
+
+    #[test]
+    fn detects_rotational_symmetry() {
+        let rotational = pattern("#..\n.#.\n..#\n#.#");
+        assert!(!has_symmetry(&rotational, Symmetry::Rotational));
+
+        let rotational = pattern("##.\n...\n.##");
+        assert!(has_symmetry(&rotational, Symmetry::Rotational));
+    }
+
+    #[test]
+    fn detects_diagonal_symmetry() {
+        let main_diagonal = pattern("#.#\n..#\n###");
+        assert!(has_symmetry(&main_diagonal, Symmetry::MainDiagonal));
+        assert!(!has_symmetry(&main_diagonal, Symmetry::AntiDiagonal));
+
+        let anti_diagonal = pattern("##.\n..#\n#.#");
+        assert!(has_symmetry(&anti_diagonal, Symmetry::AntiDiagonal));
+        assert!(!has_symmetry(&anti_diagonal, Symmetry::MainDiagonal));
+    }
+
+    #[test]
+    fn diagonal_symmetry_needs_a_square_pattern() {
+        let rectangle = pattern("....\n....");
+        assert!(has_symmetry(&rectangle, Symmetry::Rotational));
+        assert!(!has_symmetry(&rectangle, Symmetry::MainDiagonal));
+    }
+}
+
+#[cfg(test)]
+mod largest_symmetric_regions_tests {
+    use super::*;
+
+    fn pattern(rows: &str) -> Vec<Vec<char>> {
+        rows.lines().map(|line| line.chars().collect()).collect()
+    }
+
+    #[test]
+    fn finds_largest_symmetric_regions() {
+        let pattern = pattern("#..#\n.#..\n#.##");
+
+        assert_eq!(
+            vec![BoundingBox {
+                top: 0,
+                left: 1,
+                bottom: 1,
+                right: 3,
+            }],
+            largest_symmetric_regions(&pattern, Symmetry::Rotational)
+        );
+        assert_eq!(
+            vec![BoundingBox {
+                top: 0,
+                left: 0,
+                bottom: 2,
+                right: 2,
+            }],
+            largest_symmetric_regions(&pattern, Symmetry::AntiDiagonal)
+        );
+    }
+
+    #[test]
+    fn reports_every_region_of_the_largest_size() {
+        let pattern = pattern("#..#\n.#..\n#.##");
+
+        let corners: Vec<_> = largest_symmetric_regions(&pattern, Symmetry::MainDiagonal)
+            .iter()
+            .map(|region| [region.top, region.left, region.bottom, region.right])
+            .collect();
+
+        assert_eq!(vec![[0, 0, 1, 1], [1, 0, 2, 1], [1, 1, 2, 2]], corners);
+    }
+}
@@ -1,12 +1,26 @@
+use bitboard::Platform;
+#[cfg(test)]
 use std::collections::HashMap;
 use std::fs;
 
+pub mod bitboard;
+pub mod program;
+
 pub fn run() {
     let contents = fs::read_to_string("14.txt").unwrap();
-    println!("pt1: {}", pt1(&contents));
-    println!("pt2: {}", pt2(&contents));
+
+    let mut platform = Platform::parse(&contents);
+    platform.tilt_north();
+    println!("pt1: {}", platform.north_load());
+
+    let platform = Platform::parse(&contents);
+    println!("pt2: {}", platform.load_after_spin_cycles(1_000_000_000));
 }
 
+// The functions below are the original Vec<Vec<char>> implementation, kept
+// as a test oracle for the bitboard platform.
+
+#[cfg(test)]
 fn pt1(contents: &str) -> usize {
     let mut answer = 0;
 
@@ -23,6 +37,7 @@ fn pt1(contents: &str) -> usize {
     answer
 }
 
+#[cfg(test)]
 fn pt2(contents: &str) -> usize {
     let mut answer = 0;
 
@@ -113,6 +128,7 @@ fn pt2(contents: &str) -> usize {
     answer
 }
 
+#[cfg(test)]
 fn parse(contents: &str) -> Vec<Vec<char>> {
     let mut grid: Vec<Vec<char>> = vec![];
 
@@ -124,6 +140,7 @@ fn parse(contents: &str) -> Vec<Vec<char>> {
     grid
 }
 
+#[cfg(test)]
 fn transpose(grid: &Vec<Vec<char>>) -> Vec<Vec<char>> {
     let height = grid.len();
     let width = grid[0].len();
@@ -141,12 +158,14 @@ fn transpose(grid: &Vec<Vec<char>>) -> Vec<Vec<char>> {
     transposed
 }
 
+#[cfg(test)]
 fn tilt_east(platform: &mut Vec<Vec<char>>) -> () {
     for i in 0..platform.len() {
         tilt_row_east(&mut platform[i]);
     }
 }
 
+#[cfg(test)]
 fn tilt_row_east(row: &mut Vec<char>) -> () {
     for i in 0..row.len() {
         let char = row[i];
@@ -167,6 +186,7 @@ fn tilt_row_east(row: &mut Vec<char>) -> () {
     }
 }
 
+#[cfg(test)]
 fn row_load(row: &Vec<char>) -> usize {
     let mut answer = 0;
     for i in 0..row.len() {
@@ -177,6 +197,7 @@ fn row_load(row: &Vec<char>) -> usize {
     answer
 }
 
+#[cfg(test)]
 fn platform_as_key(platform: &Vec<Vec<char>>) -> String {
     platform
         .iter()
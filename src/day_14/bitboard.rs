@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+
+const MAX_SIZE: usize = 128;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Direction {
+    North,
+    West,
+    South,
+    East,
+}
+
+const SPIN_CYCLE: [Direction; 4] = [
+    Direction::North,
+    Direction::West,
+    Direction::South,
+    Direction::East,
+];
+
+// Each row is stored as a bitset with bit j set when column j holds a rock.
+// Tilting north or south moves every column of a row at once, tilting east or
+// west packs each run of cells between cube rocks using a popcount.
+#[derive(Clone, Debug)]
+pub struct Platform {
+    width: usize,
+    round: Vec<u128>,
+    cube: Vec<u128>,
+    segments: Vec<Vec<u128>>,
+}
+
+impl Platform {
+    pub fn parse(contents: &str) -> Platform {
+        let mut width = 0;
+        let mut round = vec![];
+        let mut cube = vec![];
+
+        for line in contents.lines() {
+            width = line.len();
+            assert!(
+                width <= MAX_SIZE,
+                "Platform wider than {MAX_SIZE} columns: {width}"
+            );
+
+            let mut round_row = 0;
+            let mut cube_row = 0;
+            for (j, char) in line.chars().enumerate() {
+                match char {
+                    'O' => round_row |= 1 << j,
+                    '#' => cube_row |= 1 << j,
+                    _ => (),
+                }
+            }
+            round.push(round_row);
+            cube.push(cube_row);
+        }
+
+        let segments = cube.iter().map(|row| segments(*row, width)).collect();
+
+        Platform {
+            width,
+            round,
+            cube,
+            segments,
+        }
+    }
+
+    pub fn tilt(&mut self, direction: Direction) {
+        match direction {
+            Direction::North => self.tilt_north(),
+            Direction::West => self.tilt_west(),
+            Direction::South => self.tilt_south(),
+            Direction::East => self.tilt_east(),
+        }
+    }
+
+    pub fn tilt_north(&mut self) {
+        for r in 0..self.round.len() {
+            let mut moving = self.round[r];
+            self.round[r] = 0;
+            let mut k = r;
+            while moving != 0 && k > 0 {
+                let free = moving & !(self.round[k - 1] | self.cube[k - 1]);
+                self.round[k] |= moving & !free;
+                moving = free;
+                k -= 1;
+            }
+            self.round[k] |= moving;
+        }
+    }
+
+    pub fn tilt_south(&mut self) {
+        let height = self.round.len();
+        for r in (0..height).rev() {
+            let mut moving = self.round[r];
+            self.round[r] = 0;
+            let mut k = r;
+            while moving != 0 && k < height - 1 {
+                let free = moving & !(self.round[k + 1] | self.cube[k + 1]);
+                self.round[k] |= moving & !free;
+                moving = free;
+                k += 1;
+            }
+            self.round[k] |= moving;
+        }
+    }
+
+    pub fn tilt_west(&mut self) {
+        for (row, segments) in self.round.iter_mut().zip(&self.segments) {
+            let mut tilted = 0;
+            for segment in segments {
+                let count = (*row & segment).count_ones();
+                tilted |= low_bits(count) << segment.trailing_zeros();
+            }
+            *row = tilted;
+        }
+    }
+
+    pub fn tilt_east(&mut self) {
+        for (row, segments) in self.round.iter_mut().zip(&self.segments) {
+            let mut tilted = 0;
+            for segment in segments {
+                let count = (*row & segment).count_ones();
+                let end = u128::BITS - segment.leading_zeros();
+                tilted |= low_bits(count) << (end - count);
+            }
+            *row = tilted;
+        }
+    }
+
+    pub fn spin_cycle(&mut self) {
+        for direction in SPIN_CYCLE {
+            self.tilt(direction);
+        }
+    }
+
+    // Applies the sequence of tilts `times` times over, skipping ahead once
+    // the rocks settle into a repeating loop.
+    pub fn repeat(&mut self, directions: &[Direction], times: usize) {
+        let mut previously_seen = HashMap::new();
+
+        let mut repetition = 0;
+        while repetition < times {
+            if let Some(previous) = previously_seen.insert(self.round.clone(), repetition) {
+                let remaining = (times - repetition) % (repetition - previous);
+                for _ in 0..remaining {
+                    for direction in directions {
+                        self.tilt(*direction);
+                    }
+                }
+                return;
+            }
+            for direction in directions {
+                self.tilt(*direction);
+            }
+            repetition += 1;
+        }
+    }
+
+    pub fn north_load(&self) -> usize {
+        self.load(Direction::North)
+    }
+
+    pub fn load(&self, wall: Direction) -> usize {
+        let height = self.round.len();
+        let width = self.width;
+
+        self.round
+            .iter()
+            .enumerate()
+            .map(|(r, row)| match wall {
+                Direction::North => row.count_ones() as usize * (height - r),
+                Direction::South => row.count_ones() as usize * (r + 1),
+                Direction::West => bits(*row).map(|j| width - j).sum(),
+                Direction::East => bits(*row).map(|j| j + 1).sum(),
+            })
+            .sum()
+    }
+
+    pub fn load_after_spin_cycles(&self, cycles: usize) -> usize {
+        let mut platform = self.clone();
+        platform.repeat(&SPIN_CYCLE, cycles);
+        platform.north_load()
+    }
+
+    pub fn to_text(&self) -> String {
+        self.round
+            .iter()
+            .zip(&self.cube)
+            .map(|(round, cube)| {
+                (0..self.width)
+                    .map(|j| {
+                        if round & (1 << j) != 0 {
+                            'O'
+                        } else if cube & (1 << j) != 0 {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn bits(row: u128) -> impl Iterator<Item = usize> {
+    (0..u128::BITS as usize).filter(move |j| row & (1 << j) != 0)
+}
+
+fn segments(cube_row: u128, width: usize) -> Vec<u128> {
+    let mut segments = vec![];
+    let mut start = 0;
+
+    for j in 0..=width {
+        if j == width || cube_row & (1 << j) != 0 {
+            if j > start {
+                segments.push(low_bits((j - start) as u32) << start);
+            }
+            start = j + 1;
+        }
+    }
+
+    segments
+}
+
+fn low_bits(count: u32) -> u128 {
+    if count >= u128::BITS {
+        u128::MAX
+    } else {
+        (1 << count) - 1
+    }
+}
+
+#[cfg(test)]
+mod platform_tests {
+    use super::super::{parse, pt1, pt2, row_load, tilt_east, transpose};
+    use super::*;
+
+    const EXAMPLE: &str = "O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....";
+
+    #[test]
+    fn tilt_north_matches_pt1() {
+        let mut platform = Platform::parse(EXAMPLE);
+        platform.tilt_north();
+        assert_eq!(pt1(EXAMPLE), platform.north_load());
+    }
+
+    #[test]
+    fn spin_cycles_match_transpose_and_tilt() {
+        let mut platform = Platform::parse(EXAMPLE);
+        let mut oracle = transpose(&parse(EXAMPLE));
+
+        for _ in 0..50 {
+            platform.spin_cycle();
+            for _ in 0..4 {
+                tilt_east(&mut oracle);
+                oracle = transpose(&oracle);
+            }
+
+            let oracle_load: usize = oracle.iter().map(row_load).sum();
+            assert_eq!(oracle_load, platform.north_load());
+        }
+    }
+
+    #[test]
+    fn tilts_each_direction() {
+        let contents = ".O..\nO.#.\n...O";
+
+        let mut platform = Platform::parse(contents);
+        platform.tilt_north();
+        assert_eq!(vec![0b1011, 0b0000, 0b0000], platform.round);
+
+        let mut platform = Platform::parse(contents);
+        platform.tilt_south();
+        assert_eq!(vec![0b0000, 0b0000, 0b1011], platform.round);
+
+        let mut platform = Platform::parse(contents);
+        platform.tilt_west();
+        assert_eq!(vec![0b0001, 0b0001, 0b0001], platform.round);
+
+        let mut platform = Platform::parse(contents);
+        platform.tilt_east();
+        assert_eq!(vec![0b1000, 0b0010, 0b1000], platform.round);
+    }
+
+    #[test]
+    fn load_after_spin_cycles_matches_pt2() {
+        let platform = Platform::parse(EXAMPLE);
+        assert_eq!(pt2(EXAMPLE), platform.load_after_spin_cycles(1_000_000_000));
+    }
+
+    #[test]
+    fn simulates_a_million_spin_cycles_directly() {
+        let mut platform = Platform::parse(EXAMPLE);
+        for _ in 0..1_000_000 {
+            platform.spin_cycle();
+        }
+        assert_eq!(
+            platform.north_load(),
+            Platform::parse(EXAMPLE).load_after_spin_cycles(1_000_000)
+        );
+    }
+
+    #[test]
+    fn measures_load_against_each_wall() {
+        let platform = Platform::parse(".O..\nO.#.\n...O");
+
+        assert_eq!(3 + 2 + 1, platform.load(Direction::North));
+        assert_eq!(1 + 2 + 3, platform.load(Direction::South));
+        assert_eq!(3 + 4 + 1, platform.load(Direction::West));
+        assert_eq!(2 + 1 + 4, platform.load(Direction::East));
+    }
+
+    #[test]
+    fn renders_platform_as_text() {
+        let mut platform = Platform::parse(EXAMPLE);
+        platform.tilt_north();
+
+        let expected = "OOOO.#.O..
+OO..#....#
+OO..O##..O
+O..#.OO...
+........#.
+..#....#.#
+..O..#.O.O
+..O.......
+#....###..
+#....#....";
+
+        assert_eq!(expected, platform.to_text());
+    }
+}
@@ -0,0 +1,119 @@
+use super::bitboard::{Direction, Platform};
+
+#[derive(Debug, PartialEq)]
+pub enum TiltProgramError {
+    Empty,
+    UnknownInstruction { position: usize, instruction: char },
+}
+
+// A program is a sequence of N, W, S and E tilts, optionally separated by
+// whitespace, e.g. "N W S E" for a spin cycle.
+#[derive(Debug, PartialEq)]
+pub struct TiltProgram {
+    directions: Vec<Direction>,
+}
+
+impl TiltProgram {
+    pub fn parse(source: &str) -> Result<TiltProgram, TiltProgramError> {
+        let mut directions = vec![];
+
+        for (position, instruction) in source.chars().enumerate() {
+            let direction = match instruction {
+                'N' => Direction::North,
+                'W' => Direction::West,
+                'S' => Direction::South,
+                'E' => Direction::East,
+                _ if instruction.is_whitespace() => continue,
+                _ => {
+                    return Err(TiltProgramError::UnknownInstruction {
+                        position,
+                        instruction,
+                    })
+                }
+            };
+            directions.push(direction);
+        }
+
+        if directions.is_empty() {
+            return Err(TiltProgramError::Empty);
+        }
+
+        Ok(TiltProgram { directions })
+    }
+
+    pub fn directions(&self) -> &[Direction] {
+        &self.directions
+    }
+
+    pub fn run(&self, platform: &mut Platform, times: usize) {
+        platform.repeat(&self.directions, times);
+    }
+}
+
+#[cfg(test)]
+mod tilt_program_tests {
+    use super::*;
+
+    const EXAMPLE: &str = "O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....";
+
+    #[test]
+    fn parses_programs_with_or_without_whitespace() {
+        let expected = vec![Direction::North, Direction::North, Direction::East];
+
+        assert_eq!(expected, TiltProgram::parse("N N E").unwrap().directions());
+        assert_eq!(expected, TiltProgram::parse("NNE").unwrap().directions());
+    }
+
+    #[test]
+    fn rejects_empty_and_unknown_programs() {
+        assert_eq!(Err(TiltProgramError::Empty), TiltProgram::parse("  "));
+        assert_eq!(
+            Err(TiltProgramError::UnknownInstruction {
+                position: 2,
+                instruction: 'X'
+            }),
+            TiltProgram::parse("N X")
+        );
+    }
+
+    #[test]
+    fn spin_cycle_program_matches_pt2() {
+        let program = TiltProgram::parse("N W S E").unwrap();
+        let mut platform = Platform::parse(EXAMPLE);
+
+        program.run(&mut platform, 1_000_000_000);
+
+        assert_eq!(64, platform.load(Direction::North));
+    }
+
+    #[test]
+    fn runs_arbitrary_programs() {
+        let program = TiltProgram::parse("N N E").unwrap();
+        let mut platform = Platform::parse(EXAMPLE);
+
+        program.run(&mut platform, 3);
+
+        let expected = ".OOOO#..OO
+..OO#...O#
+..OOO##..O
+..O#.....O
+........#.
+..#....#O#
+.....#...O
+.........O
+#....###..
+#....#....";
+
+        assert_eq!(expected, platform.to_text());
+        assert_eq!(145, platform.load(Direction::North));
+    }
+}
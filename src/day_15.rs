@@ -1,5 +1,9 @@
+use holiday_map::HolidayMap;
 use std::fs;
 
+pub mod holiday_map;
+pub mod trace;
+
 pub fn run() {
     let contents = fs::read_to_string("15.txt").unwrap();
     let contents = contents.trim_end();
@@ -20,24 +24,22 @@ fn pt1(contents: &str) -> usize {
 }
 
 fn pt2(contents: &str) -> usize {
-    let mut boxes = Boxes::new();
+    let mut lenses = HolidayMap::new();
 
     let initialization_sequence = contents.split(",");
 
     for step in initialization_sequence {
-        let (label, operator, focal_length) = parse_step(step);
-        let box_hash = hash(&label);
-        match operator {
-            '-' => boxes.remove_lens(box_hash, label),
-            '=' => {
-                let lens = Lens::new(focal_length.unwrap(), label);
-                boxes.add_lens(box_hash, lens);
-            }
-            _ => panic!("unexpected operator"),
-        }
+        apply_step(&mut lenses, step);
     }
 
-    boxes.focusing_power()
+    focusing_power(&lenses)
+}
+
+fn focusing_power(lenses: &HolidayMap<&str, usize>) -> usize {
+    lenses
+        .iter_slots()
+        .map(|(box_index, slot, _, focal_length)| (box_index + 1) * (slot + 1) * focal_length)
+        .sum()
 }
 
 fn hash(step: &str) -> usize {
@@ -52,123 +54,59 @@ fn hash(step: &str) -> usize {
     value
 }
 
-fn parse_step(step: &str) -> (&str, char, Option<usize>) {
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Operation {
+    Remove,
+    Insert(usize),
+}
+
+fn parse_step(step: &str) -> (&str, Operation) {
     if let Some((label, focal_length)) = step.split_once("=") {
-        (label, '=', Some(focal_length.parse().unwrap()))
+        (label, Operation::Insert(focal_length.parse().unwrap()))
     } else {
         let (label, _) = step.split_once("-").unwrap();
-        (label, '-', None)
+        (label, Operation::Remove)
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-struct Lens<'a> {
-    focal_length: usize,
-    label: &'a str,
-}
-
-impl<'a> Lens<'a> {
-    fn new(focal_length: usize, label: &'a str) -> Lens<'a> {
-        Lens {
-            focal_length,
-            label,
+fn apply_step<'a>(lenses: &mut HolidayMap<&'a str, usize>, step: &'a str) -> (&'a str, Operation) {
+    let (label, operation) = parse_step(step);
+    match operation {
+        Operation::Remove => {
+            lenses.remove(&label);
         }
-    }
-}
-
-#[derive(Clone, Copy, Debug)]
-struct Box<'a> {
-    lenses: [Option<Lens<'a>>; 5],
-}
-
-impl<'a> Box<'a> {
-    fn new() -> Box<'a> {
-        Box { lenses: [None; 5] }
-    }
-
-    fn remove_lens(&mut self, label: &str) {
-        let mut removed = false;
-
-        for i in 0..self.lenses.len() {
-            match self.lenses[i] {
-                Some(lens) => {
-                    if lens.label == label {
-                        self.lenses[i] = None;
-                        removed = true;
-                    } else if removed {
-                        self.lenses[i - 1] = Some(lens);
-                        self.lenses[i] = None;
-                    }
-                }
-                None => break,
-            }
-        }
-    }
-
-    fn add_lens(&mut self, new_lens: Lens<'a>) {
-        for i in 0..self.lenses.len() {
-            match self.lenses[i] {
-                Some(lens) => {
-                    if lens.label == new_lens.label {
-                        self.lenses[i] = Some(new_lens);
-                        break;
-                    }
-                }
-                None => {
-                    self.lenses[i] = Some(new_lens);
-                    break;
-                }
-            }
-        }
-    }
-
-    fn focusing_power(&self) -> usize {
-        let mut sum = 0;
-        for (slot_number, lens) in self.lenses.iter().enumerate() {
-            match lens {
-                Some(lens) => {
-                    let slot_number = 1 + slot_number;
-                    sum += slot_number * lens.focal_length;
-                }
-                None => break,
-            }
+        Operation::Insert(focal_length) => {
+            lenses.insert(label, focal_length);
         }
-        sum
     }
+    (label, operation)
 }
 
-struct Boxes<'a> {
-    by_hash: [Box<'a>; 256],
-}
+#[cfg(test)]
+mod pt2_tests {
+    use super::*;
 
-impl<'a> Boxes<'a> {
-    fn new() -> Boxes<'a> {
-        Boxes {
-            by_hash: [Box::new(); 256],
-        }
+    #[test]
+    fn example_input() {
+        let contents = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+        assert_eq!(145, pt2(contents));
     }
 
-    fn remove_lens(&mut self, box_hash: usize, label: &str) {
-        let mut r#box = self.by_hash[box_hash];
-        r#box.remove_lens(label);
-        self.by_hash[box_hash] = r#box;
-    }
+    #[test]
+    fn more_than_five_lenses_in_one_box() {
+        let contents = "ahj=1,awk=2,bfk=3,bul=4,cdl=5,csm=6";
+        assert_eq!(
+            4 * (1 + 2 * 2 + 3 * 3 + 4 * 4 + 5 * 5 + 6 * 6),
+            pt2(contents)
+        );
 
-    fn add_lens(&mut self, box_hash: usize, new_lens: Lens<'a>) {
-        let mut r#box = self.by_hash[box_hash];
-        r#box.add_lens(new_lens);
-        self.by_hash[box_hash] = r#box;
+        let contents = "ahj=1,awk=2,bfk=3,bul=4,cdl=5,csm=6,awk-,cdl=9";
+        assert_eq!(4 * (1 + 2 * 3 + 3 * 4 + 4 * 9 + 5 * 6), pt2(contents));
     }
 
-    fn focusing_power(&self) -> usize {
-        let mut sum = 0;
-        for (box_hash, r#box) in self.by_hash.iter().enumerate() {
-            let box_focusing_power = r#box.focusing_power();
-            if box_focusing_power > 0 {
-                let box_number = 1 + box_hash;
-                sum += box_number * box_focusing_power;
-            }
-        }
-        sum
+    #[test]
+    fn keeps_insertion_order_after_compacting() {
+        let contents = "ahj=1,awk=2,bfk=3,ahj-,awk-,dbm=4,bfk=5";
+        assert_eq!(4 * (5 + 2 * 4), pt2(contents));
     }
 }
@@ -0,0 +1,212 @@
+use super::hash;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+const BOX_COUNT: usize = 256;
+
+// An insertion-ordered map that files each entry into one of 256 boxes using
+// the puzzle's HASH algorithm, or any other hasher given to `with_hasher`.
+pub struct HolidayMap<K, V> {
+    boxes: Vec<Slots<K, V>>,
+    hasher: fn(&K) -> u8,
+    len: usize,
+}
+
+impl<K: AsRef<str> + Eq + Hash + Clone, V> HolidayMap<K, V> {
+    pub fn new() -> HolidayMap<K, V> {
+        HolidayMap::with_hasher(|key| hash(key.as_ref()) as u8)
+    }
+}
+
+impl<K: AsRef<str> + Eq + Hash + Clone, V> Default for HolidayMap<K, V> {
+    fn default() -> Self {
+        HolidayMap::new()
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> HolidayMap<K, V> {
+    pub fn with_hasher(hasher: fn(&K) -> u8) -> HolidayMap<K, V> {
+        HolidayMap {
+            boxes: (0..BOX_COUNT).map(|_| Slots::new()).collect(),
+            hasher,
+            len: 0,
+        }
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let box_index = self.box_index(&key);
+        let previous = self.boxes[box_index].insert(key, value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let box_index = self.box_index(key);
+        let removed = self.boxes[box_index].remove(key);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let box_index = self.box_index(key);
+        self.boxes[box_index].get(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn box_index(&self, key: &K) -> usize {
+        (self.hasher)(key) as usize
+    }
+
+    pub fn box_entries(&self, box_index: usize) -> impl Iterator<Item = (&K, &V)> {
+        self.boxes[box_index].iter()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.iter_slots().map(|(_, _, key, value)| (key, value))
+    }
+
+    // Yields (box, slot, key, value) in box-then-slot order, with boxes and
+    // slots both numbered from 0.
+    pub fn iter_slots(&self) -> impl Iterator<Item = (usize, usize, &K, &V)> {
+        self.boxes
+            .iter()
+            .enumerate()
+            .flat_map(|(box_index, slots)| {
+                slots
+                    .iter()
+                    .enumerate()
+                    .map(move |(slot, (key, value))| (box_index, slot, key, value))
+            })
+    }
+}
+
+// Removed entries leave an empty slot behind so removal doesn't shift every
+// entry after it. The slots are compacted once over half of them are empty.
+struct Slots<K, V> {
+    entries: Vec<Option<(K, V)>>,
+    slots_by_key: HashMap<K, usize>,
+    empty_slot_count: usize,
+}
+
+impl<K: Eq + Hash + Clone, V> Slots<K, V> {
+    fn new() -> Slots<K, V> {
+        Slots {
+            entries: vec![],
+            slots_by_key: HashMap::new(),
+            empty_slot_count: 0,
+        }
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(slot) = self.slots_by_key.get(&key) {
+            let (_, previous) = self.entries[*slot].replace((key, value)).unwrap();
+            Some(previous)
+        } else {
+            self.slots_by_key.insert(key.clone(), self.entries.len());
+            self.entries.push(Some((key, value)));
+            None
+        }
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let slot = self.slots_by_key.remove(key)?;
+        let (_, removed) = self.entries[slot].take().unwrap();
+        self.empty_slot_count += 1;
+
+        if self.empty_slot_count * 2 > self.entries.len() {
+            self.compact();
+        }
+
+        Some(removed)
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        let slot = self.slots_by_key.get(key)?;
+        self.entries[*slot].as_ref().map(|(_, value)| value)
+    }
+
+    fn compact(&mut self) {
+        self.entries.retain(|entry| entry.is_some());
+        for (slot, (key, _)) in self.entries.iter().flatten().enumerate() {
+            self.slots_by_key.insert(key.clone(), slot);
+        }
+        self.empty_slot_count = 0;
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries
+            .iter()
+            .flatten()
+            .map(|(key, value)| (key, value))
+    }
+}
+
+#[cfg(test)]
+mod holiday_map_tests {
+    use super::*;
+
+    #[test]
+    fn insert_get_and_remove() {
+        let mut map = HolidayMap::new();
+
+        assert_eq!(None, map.insert("rn", 1));
+        assert_eq!(None, map.insert("cm", 2));
+        assert_eq!(Some(1), map.insert("rn", 3));
+
+        assert_eq!(2, map.len());
+        assert_eq!(Some(&3), map.get(&"rn"));
+        assert_eq!(Some(2), map.remove(&"cm"));
+        assert_eq!(None, map.remove(&"cm"));
+        assert_eq!(None, map.get(&"cm"));
+        assert_eq!(1, map.len());
+    }
+
+    #[test]
+    fn iterates_in_box_then_slot_order() {
+        let mut map = HolidayMap::new();
+
+        for (label, focal_length) in [("ot", 9), ("rn", 1), ("ab", 5), ("cm", 2), ("pc", 6)] {
+            map.insert(label, focal_length);
+        }
+
+        let slots: Vec<_> = map
+            .iter_slots()
+            .map(|(box_index, slot, label, _)| (box_index, slot, *label))
+            .collect();
+
+        assert_eq!(
+            vec![
+                (0, 0, "rn"),
+                (0, 1, "cm"),
+                (3, 0, "ot"),
+                (3, 1, "ab"),
+                (3, 2, "pc")
+            ],
+            slots
+        );
+    }
+
+    #[test]
+    fn uses_a_pluggable_hasher() {
+        let mut map = HolidayMap::with_hasher(|key: &u32| (key % 2) as u8);
+
+        map.insert(3, "three");
+        map.insert(2, "two");
+        map.insert(1, "one");
+
+        let keys: Vec<_> = map.iter().map(|(key, _)| *key).collect();
+
+        assert_eq!(vec![2, 3, 1], keys);
+    }
+}
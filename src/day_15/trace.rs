@@ -0,0 +1,191 @@
+use super::holiday_map::HolidayMap;
+use super::{apply_step, Operation};
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq)]
+pub struct TraceStep<'a> {
+    pub step: &'a str,
+    pub label: &'a str,
+    pub hash: usize,
+    pub operation: Operation,
+    pub box_contents: Vec<(&'a str, usize)>,
+}
+
+#[derive(Debug)]
+pub struct Trace<'a> {
+    pub steps: Vec<TraceStep<'a>>,
+}
+
+pub fn trace(contents: &str) -> Trace<'_> {
+    let mut lenses = HolidayMap::new();
+    let mut steps = vec![];
+
+    for step in contents.split(",") {
+        let (label, operation) = apply_step(&mut lenses, step);
+        let hash = lenses.box_index(&label);
+        let box_contents = lenses
+            .box_entries(hash)
+            .map(|(label, focal_length)| (*label, *focal_length))
+            .collect();
+
+        steps.push(TraceStep {
+            step,
+            label,
+            hash,
+            operation,
+            box_contents,
+        });
+    }
+
+    Trace { steps }
+}
+
+impl<'a> Trace<'a> {
+    // Each step only changes its own box, so the state of every box after a
+    // step is rebuilt from the latest snapshot of each box up to that point.
+    // None if there is no such step.
+    pub fn state_after(&self, step_index: usize) -> Option<BTreeMap<usize, &[(&'a str, usize)]>> {
+        let mut boxes = BTreeMap::new();
+
+        for step in self.steps.get(..=step_index)? {
+            step.apply(&mut boxes);
+        }
+
+        Some(boxes)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut boxes = BTreeMap::new();
+
+        self.steps
+            .iter()
+            .map(|step| {
+                step.apply(&mut boxes);
+
+                let mut text = format!("After \"{}\":", step.step);
+                for (hash, lenses) in &boxes {
+                    let lenses: Vec<_> = lenses
+                        .iter()
+                        .map(|(label, focal_length)| format!("[{label} {focal_length}]"))
+                        .collect();
+                    text += &format!("\nBox {hash}: {}", lenses.join(" "));
+                }
+                text
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    pub fn state_json(&self, step_index: usize) -> Option<String> {
+        let boxes: Vec<_> = self
+            .state_after(step_index)?
+            .into_iter()
+            .map(|(hash, lenses)| {
+                let lenses: Vec<_> = lenses
+                    .iter()
+                    .map(|(label, focal_length)| {
+                        format!(
+                            "{{\"label\":{},\"focal_length\":{focal_length}}}",
+                            json_string(label)
+                        )
+                    })
+                    .collect();
+                format!("{{\"box\":{hash},\"lenses\":[{}]}}", lenses.join(","))
+            })
+            .collect();
+
+        Some(format!("[{}]", boxes.join(",")))
+    }
+}
+
+impl<'a> TraceStep<'a> {
+    fn apply<'b>(&'b self, boxes: &mut BTreeMap<usize, &'b [(&'a str, usize)]>) {
+        if self.box_contents.is_empty() {
+            boxes.remove(&self.hash);
+        } else {
+            boxes.insert(self.hash, self.box_contents.as_slice());
+        }
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+    for char in value.chars() {
+        match char {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            char if char.is_control() => json.push_str(&format!("\\u{:04x}", char as u32)),
+            char => json.push(char),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod trace_tests {
+    use super::*;
+
+    const EXAMPLE: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+
+    #[test]
+    fn records_each_step() {
+        let trace = trace(EXAMPLE);
+
+        assert_eq!(11, trace.steps.len());
+        assert_eq!(
+            TraceStep {
+                step: "ab=5",
+                label: "ab",
+                hash: 3,
+                operation: Operation::Insert(5),
+                box_contents: vec![("pc", 4), ("ot", 9), ("ab", 5)],
+            },
+            trace.steps[7]
+        );
+        assert_eq!(Operation::Remove, trace.steps[8].operation);
+        assert_eq!(vec![("ot", 9), ("ab", 5)], trace.steps[8].box_contents);
+    }
+
+    #[test]
+    fn prints_in_puzzle_format() {
+        let trace = trace("rn=1,cm-,qp=3,cm=2,qp-");
+
+        let expected = "After \"rn=1\":
+Box 0: [rn 1]
+
+After \"cm-\":
+Box 0: [rn 1]
+
+After \"qp=3\":
+Box 0: [rn 1]
+Box 1: [qp 3]
+
+After \"cm=2\":
+Box 0: [rn 1] [cm 2]
+Box 1: [qp 3]
+
+After \"qp-\":
+Box 0: [rn 1] [cm 2]";
+
+        assert_eq!(expected, trace.to_text());
+    }
+
+    #[test]
+    fn exports_state_as_json() {
+        let trace = trace(EXAMPLE);
+
+        assert_eq!(
+            "[{\"box\":0,\"lenses\":[{\"label\":\"rn\",\"focal_length\":1}]},\
+             {\"box\":1,\"lenses\":[{\"label\":\"qp\",\"focal_length\":3}]}]",
+            trace.state_json(2).unwrap()
+        );
+        assert_eq!(
+            "[{\"box\":0,\"lenses\":[{\"label\":\"rn\",\"focal_length\":1},{\"label\":\"cm\",\"focal_length\":2}]},\
+             {\"box\":3,\"lenses\":[{\"label\":\"ot\",\"focal_length\":7},{\"label\":\"ab\",\"focal_length\":5},{\"label\":\"pc\",\"focal_length\":6}]}]",
+            trace.state_json(10).unwrap()
+        );
+        assert_eq!(None, trace.state_json(11));
+        assert_eq!(None, trace.state_after(11));
+    }
+}
@@ -1,4 +1,8 @@
 use std::fs;
+use sweep::best_start;
+
+pub mod render;
+pub mod sweep;
 
 pub fn run() {
     let example_input = fs::read_to_string("16_example.txt").unwrap();
@@ -19,8 +23,14 @@ fn pt1<const N: usize>(input: &str) -> usize {
 }
 
 fn pt2<const N: usize>(input: &str) -> usize {
-    let mut contraption: Contraption<N> = Contraption::from(input);
+    let contraption: Contraption<N> = Contraption::from(input);
+
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
 
+    best_start(&contraption, threads).energized_tiles
+}
+
+pub fn edge_starts<const N: usize>() -> Vec<((usize, usize), Direction)> {
     let mut starts = vec![];
 
     for i in 0..N {
@@ -30,9 +40,16 @@ fn pt2<const N: usize>(input: &str) -> usize {
         starts.push(((i, N - 1), Direction::Up));
     }
 
+    starts
+}
+
+#[cfg(test)]
+fn max_energized_by_resimulating<const N: usize>(input: &str) -> usize {
+    let mut contraption: Contraption<N> = Contraption::from(input);
+
     let mut max_energized_tiles = 0;
 
-    for (start_coords, direction) in starts {
+    for (start_coords, direction) in edge_starts::<N>() {
         contraption.reset_energized_tiles();
         contraption.start_beam(start_coords, direction);
 
@@ -46,98 +63,97 @@ fn pt2<const N: usize>(input: &str) -> usize {
     max_energized_tiles
 }
 
-struct Contraption<const N: usize> {
+pub struct Contraption<const N: usize> {
     tiles: [[char; N]; N],
-    energized_tiles: [[bool; N]; N],
+    beam_directions: [[u8; N]; N],
 }
 
 impl<const N: usize> Contraption<N> {
-    fn start_beam(&mut self, start_coords: (usize, usize), start_direction: Direction) {
-        let (mut x, mut y) = start_coords;
-
-        let mut direction = start_direction;
-
-        loop {
-            let tile = self.tiles[y][x];
+    // Walks every beam from a worklist of (tile, direction) states. A state is
+    // only ever expanded once, so beams that loop through mirrors stop as soon
+    // as they come back round.
+    pub fn start_beam(&mut self, start_coords: (usize, usize), start_direction: Direction) {
+        let mut beams = vec![(start_coords, start_direction)];
+
+        while !beams.is_empty() {
+            beams = self.advance_beams(beams);
+        }
+    }
 
-            let energized = self.energized_tiles[y][x];
+    // Moves every beam on by one tile, returning the beams still travelling
+    fn advance_beams(
+        &mut self,
+        beams: Vec<((usize, usize), Direction)>,
+    ) -> Vec<((usize, usize), Direction)> {
+        let mut next_beams = vec![];
 
-            if let ('-' | '|', true) = (tile, energized) {
-                // means a loop is about to start and the beam will never finish travelling
-                break;
+        for ((x, y), direction) in beams {
+            if self.beam_directions[y][x] & direction.bit() != 0 {
+                continue;
             }
+            self.beam_directions[y][x] |= direction.bit();
 
-            self.energized_tiles[y][x] = true;
-
-            direction = match (tile, &direction) {
-                ('.', _) => direction,
-                ('\\', Direction::Up) => Direction::Left,
-                ('\\', Direction::Down) => Direction::Right,
-                ('\\', Direction::Left) => Direction::Up,
-                ('\\', Direction::Right) => Direction::Down,
-                ('/', Direction::Up) => Direction::Right,
-                ('/', Direction::Down) => Direction::Left,
-                ('/', Direction::Left) => Direction::Down,
-                ('/', Direction::Right) => Direction::Up,
-                ('|', Direction::Down | Direction::Up) => direction,
-                ('|', Direction::Left | Direction::Right) => {
-                    if y < N - 1 {
-                        self.start_beam((x, y + 1), Direction::Down);
-                    }
-                    Direction::Up
-                }
-                ('-', Direction::Right | Direction::Left) => direction,
-                ('-', Direction::Up | Direction::Down) => {
-                    if x < N - 1 {
-                        self.start_beam((x + 1, y), Direction::Right);
-                    }
-                    Direction::Left
-                }
-                _ => panic!("invalid tile"),
-            };
-
-            match direction {
-                Direction::Up => {
-                    if y == 0 {
-                        break;
-                    } else {
-                        y -= 1
-                    }
-                }
-                Direction::Down => {
-                    if y == N - 1 {
-                        break;
-                    } else {
-                        y += 1
-                    }
-                }
-                Direction::Left => {
-                    if x == 0 {
-                        break;
-                    } else {
-                        x -= 1
-                    }
-                }
-                Direction::Right => {
-                    if x == N - 1 {
-                        break;
-                    } else {
-                        x += 1
-                    }
+            for next_direction in deflect(self.tiles[y][x], direction) {
+                if let Some(next_coords) = step::<N>((x, y), *next_direction) {
+                    next_beams.push((next_coords, *next_direction));
                 }
             }
         }
+
+        next_beams
+    }
+
+    // The directions beams were travelling in as they entered the tile
+    pub fn beam_directions(&self, (x, y): (usize, usize)) -> Vec<Direction> {
+        DIRECTIONS
+            .into_iter()
+            .filter(|direction| self.beam_directions[y][x] & direction.bit() != 0)
+            .collect()
     }
 
-    fn total_energized_tiles(&self) -> usize {
-        self.energized_tiles
-            .map(|row| row.into_iter().filter(|is_energized| *is_energized).count())
+    pub fn total_energized_tiles(&self) -> usize {
+        self.beam_directions
             .iter()
+            .map(|row| row.iter().filter(|directions| **directions != 0).count())
             .sum()
     }
 
-    fn reset_energized_tiles(&mut self) {
-        self.energized_tiles = self.energized_tiles.map(|row| row.map(|_| false));
+    pub fn reset_energized_tiles(&mut self) {
+        self.beam_directions = [[0; N]; N];
+    }
+}
+
+fn deflect(tile: char, direction: Direction) -> &'static [Direction] {
+    match (tile, direction) {
+        ('.', Direction::Up) => &[Direction::Up],
+        ('.', Direction::Down) => &[Direction::Down],
+        ('.', Direction::Left) => &[Direction::Left],
+        ('.', Direction::Right) => &[Direction::Right],
+        ('\\', Direction::Up) => &[Direction::Left],
+        ('\\', Direction::Down) => &[Direction::Right],
+        ('\\', Direction::Left) => &[Direction::Up],
+        ('\\', Direction::Right) => &[Direction::Down],
+        ('/', Direction::Up) => &[Direction::Right],
+        ('/', Direction::Down) => &[Direction::Left],
+        ('/', Direction::Left) => &[Direction::Down],
+        ('/', Direction::Right) => &[Direction::Up],
+        ('|', Direction::Up) => &[Direction::Up],
+        ('|', Direction::Down) => &[Direction::Down],
+        ('|', Direction::Left | Direction::Right) => &[Direction::Up, Direction::Down],
+        ('-', Direction::Left) => &[Direction::Left],
+        ('-', Direction::Right) => &[Direction::Right],
+        ('-', Direction::Up | Direction::Down) => &[Direction::Left, Direction::Right],
+        _ => panic!("invalid tile"),
+    }
+}
+
+fn step<const N: usize>((x, y): (usize, usize), direction: Direction) -> Option<(usize, usize)> {
+    match direction {
+        Direction::Up if y > 0 => Some((x, y - 1)),
+        Direction::Down if y < N - 1 => Some((x, y + 1)),
+        Direction::Left if x > 0 => Some((x - 1, y)),
+        Direction::Right if x < N - 1 => Some((x + 1, y)),
+        _ => None,
     }
 }
 
@@ -146,7 +162,7 @@ impl<const N: usize> From<&str> for Contraption<N> {
         let mut lines = s.lines().map(|l| l.chars());
 
         Self {
-            energized_tiles: [[false; N]; N],
+            beam_directions: [[0; N]; N],
             tiles: std::array::from_fn(|_| {
                 let mut line = lines.next().unwrap();
                 std::array::from_fn(|_| line.next().unwrap())
@@ -155,9 +171,78 @@ impl<const N: usize> From<&str> for Contraption<N> {
     }
 }
 
-enum Direction {
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Direction {
     Up,
     Down,
     Left,
     Right,
 }
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+impl Direction {
+    fn index(&self) -> usize {
+        match self {
+            Direction::Up => 0,
+            Direction::Down => 1,
+            Direction::Left => 2,
+            Direction::Right => 3,
+        }
+    }
+
+    fn bit(&self) -> u8 {
+        1 << self.index()
+    }
+}
+
+#[cfg(test)]
+mod contraption_tests {
+    use super::*;
+
+    const EXAMPLE: &str = ".|...\\....
+|.-.\\.....
+.....|-...
+........|.
+..........
+.........\\
+..../.\\\\..
+.-.-/..|..
+.|....-|.\\
+..//.|....";
+
+    #[test]
+    fn energizes_example() {
+        assert_eq!(46, pt1::<10>(EXAMPLE));
+        assert_eq!(51, pt2::<10>(EXAMPLE));
+    }
+
+    #[test]
+    fn terminates_when_beams_loop_through_mirrors() {
+        let mut contraption: Contraption<3> = Contraption::from("/-\\\n...\n\\./");
+        contraption.start_beam((1, 0), Direction::Right);
+
+        assert_eq!(8, contraption.total_energized_tiles());
+        assert_eq!(vec![Direction::Right], contraption.beam_directions((1, 0)));
+        assert_eq!(vec![Direction::Up], contraption.beam_directions((0, 1)));
+    }
+
+    #[test]
+    fn records_each_direction_a_tile_was_crossed_in() {
+        let mut contraption: Contraption<3> = Contraption::from(".|.\n...\n.-.");
+        contraption.start_beam((0, 0), Direction::Right);
+        contraption.start_beam((0, 1), Direction::Right);
+
+        assert_eq!(
+            vec![Direction::Down, Direction::Right],
+            contraption.beam_directions((1, 1))
+        );
+        assert_eq!(vec![Direction::Down], contraption.beam_directions((1, 2)));
+        assert_eq!(vec![Direction::Right], contraption.beam_directions((1, 0)));
+    }
+}
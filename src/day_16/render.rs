@@ -0,0 +1,242 @@
+use super::{Contraption, Direction};
+use std::fs;
+use std::io;
+
+const TILE_SIZE: usize = 10;
+const FRAME_MILLIS: usize = 100;
+
+// Overlays the beams on the contraption the way the puzzle does: mirrors and
+// splitters are drawn as they are, and empty tiles show the direction of the
+// beam crossing them, or how many beams cross them when there is more than one.
+pub fn render_text<const N: usize>(contraption: &Contraption<N>) -> String {
+    (0..N)
+        .map(|y| {
+            (0..N)
+                .map(|x| {
+                    let tile = contraption.tiles[y][x];
+                    let directions = contraption.beam_directions((x, y));
+                    match (tile, directions.as_slice()) {
+                        ('.', []) => '.',
+                        ('.', [direction]) => arrow(*direction),
+                        ('.', directions) => char::from_digit(directions.len() as u32, 10).unwrap(),
+                        (tile, _) => tile,
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn render_energized<const N: usize>(contraption: &Contraption<N>) -> String {
+    contraption
+        .beam_directions
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|directions| if *directions != 0 { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// One text frame for each step the beams take, starting from an
+// unenergized copy of the contraption. Steps that only energize mirrors and
+// splitters don't change the text, so they don't get a frame of their own.
+pub fn frames<const N: usize>(
+    contraption: &Contraption<N>,
+    start_coords: (usize, usize),
+    start_direction: Direction,
+) -> Vec<String> {
+    let mut frames = vec![];
+
+    animate(contraption, start_coords, start_direction, |animated| {
+        let frame = render_text(animated);
+        if frames.last() != Some(&frame) {
+            frames.push(frame);
+        }
+    });
+
+    frames
+}
+
+pub fn render_svg_animation<const N: usize>(
+    contraption: &Contraption<N>,
+    start_coords: (usize, usize),
+    start_direction: Direction,
+) -> String {
+    let mut energized_at = [[None; N]; N];
+    let mut frame = 0;
+
+    animate(contraption, start_coords, start_direction, |animated| {
+        for (y, row) in animated.beam_directions.iter().enumerate() {
+            for (x, directions) in row.iter().enumerate() {
+                if *directions != 0 && energized_at[y][x].is_none() {
+                    energized_at[y][x] = Some(frame);
+                }
+            }
+        }
+        frame += 1;
+    });
+
+    let size = N * TILE_SIZE;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" viewBox=\"0 0 {size} {size}\">\n"
+    );
+    svg += &format!("  <rect width=\"{size}\" height=\"{size}\" fill=\"#0f0f23\"/>\n");
+
+    for (y, row) in energized_at.iter().enumerate() {
+        for (x, frame) in row.iter().enumerate() {
+            if let Some(frame) = frame {
+                svg += &format!(
+                    "  <rect x=\"{}\" y=\"{}\" width=\"{TILE_SIZE}\" height=\"{TILE_SIZE}\" fill=\"#ffff66\" opacity=\"0\"><set attributeName=\"opacity\" to=\"1\" begin=\"{}ms\" fill=\"freeze\"/></rect>\n",
+                    x * TILE_SIZE,
+                    y * TILE_SIZE,
+                    frame * FRAME_MILLIS
+                );
+            }
+        }
+    }
+
+    for (y, row) in contraption.tiles.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            if *tile != '.' {
+                svg += &format!(
+                    "  <text x=\"{}\" y=\"{}\" font-size=\"{TILE_SIZE}\" text-anchor=\"middle\" fill=\"#cccccc\">{tile}</text>\n",
+                    x * TILE_SIZE + TILE_SIZE / 2,
+                    (y + 1) * TILE_SIZE - 1
+                );
+            }
+        }
+    }
+
+    svg += "</svg>\n";
+    svg
+}
+
+pub fn export_svg_animation<const N: usize>(
+    contraption: &Contraption<N>,
+    start_coords: (usize, usize),
+    start_direction: Direction,
+    file_path: &str,
+) -> io::Result<()> {
+    fs::write(
+        file_path,
+        render_svg_animation(contraption, start_coords, start_direction),
+    )
+}
+
+fn animate<const N: usize>(
+    contraption: &Contraption<N>,
+    start_coords: (usize, usize),
+    start_direction: Direction,
+    mut on_frame: impl FnMut(&Contraption<N>),
+) {
+    let mut animated = Contraption {
+        tiles: contraption.tiles,
+        beam_directions: [[0; N]; N],
+    };
+
+    let mut beams = vec![(start_coords, start_direction)];
+
+    while !beams.is_empty() {
+        let previous = animated.beam_directions;
+        beams = animated.advance_beams(beams);
+        if animated.beam_directions != previous {
+            on_frame(&animated);
+        }
+    }
+}
+
+fn arrow(direction: Direction) -> char {
+    match direction {
+        Direction::Up => '^',
+        Direction::Down => 'v',
+        Direction::Left => '<',
+        Direction::Right => '>',
+    }
+}
+
+#[cfg(test)]
+mod render_text_tests {
+    use super::*;
+
+    const EXAMPLE: &str = ".|...\\....
+|.-.\\.....
+.....|-...
+........|.
+..........
+.........\\
+..../.\\\\..
+.-.-/..|..
+.|....-|.\\
+..//.|....";
+
+    #[test]
+    fn overlays_beams_like_the_puzzle() {
+        let mut contraption: Contraption<10> = Contraption::from(EXAMPLE);
+        contraption.start_beam((0, 0), Direction::Right);
+
+        let expected = ">|<<<\\....
+|v-.\\^....
+.v...|->>>
+.v...v^.|.
+.v...v^...
+.v...v^..\\
+.v../2\\\\..
+<->-/vv|..
+.|<<<2-|.\\
+.v//.|.v..";
+
+        assert_eq!(expected, render_text(&contraption));
+    }
+
+    #[test]
+    fn shows_energized_tiles() {
+        let mut contraption: Contraption<10> = Contraption::from(EXAMPLE);
+        contraption.start_beam((0, 0), Direction::Right);
+
+        let expected = "######....
+.#...#....
+.#...#####
+.#...##...
+.#...##...
+.#...##...
+.#..####..
+########..
+.#######..
+.#...#.#..";
+
+        assert_eq!(expected, render_energized(&contraption));
+    }
+}
+
+#[cfg(test)]
+mod animation_tests {
+    use super::*;
+
+    #[test]
+    fn emits_a_frame_per_beam_step() {
+        let contraption: Contraption<3> = Contraption::from(".|.\n...\n...");
+
+        assert_eq!(
+            vec![">|.\n...\n...", ">|.\n.v.\n...", ">|.\n.v.\n.v."],
+            frames(&contraption, (0, 0), Direction::Right)
+        );
+    }
+
+    #[test]
+    fn animates_tiles_lighting_up_in_svg() {
+        let contraption: Contraption<3> = Contraption::from(".|.\n...\n...");
+
+        let svg = render_svg_animation(&contraption, (0, 0), Direction::Right);
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"10\" height=\"10\" fill=\"#ffff66\" opacity=\"0\"><set attributeName=\"opacity\" to=\"1\" begin=\"0ms\""));
+        assert!(svg.contains("<rect x=\"10\" y=\"20\" width=\"10\" height=\"10\" fill=\"#ffff66\" opacity=\"0\"><set attributeName=\"opacity\" to=\"1\" begin=\"300ms\""));
+        assert!(!svg.contains("<rect x=\"20\" y=\"0\""));
+        assert!(svg.contains(">|</text>"));
+    }
+}
@@ -0,0 +1,363 @@
+use super::{deflect, edge_starts, step, Contraption, Direction, DIRECTIONS};
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq)]
+pub struct BestStart {
+    pub coords: (usize, usize),
+    pub direction: Direction,
+    pub energized_tiles: usize,
+}
+
+// The graph of (tile, direction) beam states condensed into its strongly
+// connected components. Every state in a component reaches exactly the same
+// tiles, so loops are only ever walked once, and a component's tiles are built
+// from its successors' so every start shares the work downstream of it.
+pub struct BeamGraph {
+    tile_count: usize,
+    component_by_state: Vec<usize>,
+    components: Vec<Component>,
+}
+
+struct Component {
+    tiles: Vec<usize>,
+    successors: Vec<usize>,
+}
+
+impl BeamGraph {
+    pub fn new<const N: usize>(contraption: &Contraption<N>) -> BeamGraph {
+        let mut successors = vec![vec![]; N * N * 4];
+
+        for y in 0..N {
+            for x in 0..N {
+                for direction in DIRECTIONS {
+                    for next_direction in deflect(contraption.tiles[y][x], direction) {
+                        if let Some(next_coords) = step::<N>((x, y), *next_direction) {
+                            successors[state::<N>((x, y), direction)]
+                                .push(state::<N>(next_coords, *next_direction));
+                        }
+                    }
+                }
+            }
+        }
+
+        let (component_by_state, component_count) = strongly_connected_components(&successors);
+
+        let mut components: Vec<_> = (0..component_count)
+            .map(|_| Component {
+                tiles: vec![],
+                successors: vec![],
+            })
+            .collect();
+
+        for (state, component) in component_by_state.iter().enumerate() {
+            components[*component].tiles.push(state / 4);
+            for next_state in &successors[state] {
+                let next_component = component_by_state[*next_state];
+                if next_component != *component {
+                    components[*component].successors.push(next_component);
+                }
+            }
+        }
+
+        for component in &mut components {
+            component.tiles.sort();
+            component.tiles.dedup();
+            component.successors.sort();
+            component.successors.dedup();
+        }
+
+        BeamGraph {
+            tile_count: N * N,
+            component_by_state,
+            components,
+        }
+    }
+
+    pub fn energized_tiles<const N: usize>(
+        &self,
+        start_coords: (usize, usize),
+        direction: Direction,
+    ) -> usize {
+        let component = self.component_by_state[state::<N>(start_coords, direction)];
+        self.energized_by_component(&[component], 1)[&component]
+    }
+
+    // Tarjan numbers every component after all the components it reaches, so
+    // walking them in ascending order lets each one OR together the tile sets
+    // of its successors rather than walking the graph again. Only components
+    // reachable from `start_components` are visited, and a tile set is dropped
+    // once every predecessor has used it. Each thread builds the tile sets for
+    // its own slice of the tiles and the counts are added up at the end.
+    fn energized_by_component(
+        &self,
+        start_components: &[usize],
+        threads: usize,
+    ) -> HashMap<usize, usize> {
+        let mut reachable = vec![false; self.components.len()];
+        let mut components = start_components.to_vec();
+        for component in start_components {
+            reachable[*component] = true;
+        }
+        while let Some(component) = components.pop() {
+            for next_component in &self.components[component].successors {
+                if !reachable[*next_component] {
+                    reachable[*next_component] = true;
+                    components.push(*next_component);
+                }
+            }
+        }
+
+        let mut uses = vec![0; self.components.len()];
+        let mut is_start = vec![false; self.components.len()];
+        for (component, _) in reachable.iter().enumerate().filter(|(_, r)| **r) {
+            for next_component in &self.components[component].successors {
+                uses[*next_component] += 1;
+            }
+        }
+        for component in start_components {
+            is_start[*component] = true;
+        }
+
+        let word_count = self.tile_count.div_ceil(64);
+        let chunk_size = word_count.div_ceil(threads.max(1)).max(1);
+        let word_ranges: Vec<_> = (0..word_count)
+            .step_by(chunk_size)
+            .map(|first| first..(first + chunk_size).min(word_count))
+            .collect();
+
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = word_ranges
+                .into_iter()
+                .map(|words| {
+                    let (reachable, is_start) = (&reachable, &is_start);
+                    let mut uses = uses.clone();
+
+                    scope.spawn(move || {
+                        let mut tile_sets: Vec<Option<Vec<u64>>> =
+                            vec![None; self.components.len()];
+                        let mut counts = vec![];
+
+                        for (id, component) in self.components.iter().enumerate() {
+                            if !reachable[id] {
+                                continue;
+                            }
+
+                            let mut tile_set = vec![0u64; words.len()];
+                            for tile in &component.tiles {
+                                if words.contains(&(tile / 64)) {
+                                    tile_set[tile / 64 - words.start] |= 1 << (tile % 64);
+                                }
+                            }
+
+                            for next_component in &component.successors {
+                                let next_tile_set = tile_sets[*next_component].as_ref().unwrap();
+                                for (word, next_word) in tile_set.iter_mut().zip(next_tile_set) {
+                                    *word |= next_word;
+                                }
+                                uses[*next_component] -= 1;
+                                if uses[*next_component] == 0 {
+                                    tile_sets[*next_component] = None;
+                                }
+                            }
+
+                            if is_start[id] {
+                                let count: u32 =
+                                    tile_set.iter().map(|word| word.count_ones()).sum();
+                                counts.push((id, count as usize));
+                            }
+                            if uses[id] > 0 {
+                                tile_sets[id] = Some(tile_set);
+                            }
+                        }
+
+                        counts
+                    })
+                })
+                .collect();
+
+            let mut energized_by_component = HashMap::new();
+            for (component, count) in handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+            {
+                *energized_by_component.entry(component).or_insert(0) += count;
+            }
+            energized_by_component
+        })
+    }
+}
+
+// Finds the edge start that energizes the most tiles, splitting the tiles
+// between `threads` threads. Ties go to the earliest start.
+pub fn best_start<const N: usize>(contraption: &Contraption<N>, threads: usize) -> BestStart {
+    let graph = BeamGraph::new(contraption);
+    let starts = edge_starts::<N>();
+
+    let mut start_components: Vec<_> = starts
+        .iter()
+        .map(|(coords, direction)| graph.component_by_state[state::<N>(*coords, *direction)])
+        .collect();
+    start_components.sort();
+    start_components.dedup();
+
+    let energized_by_component = graph.energized_by_component(&start_components, threads);
+
+    let mut best: Option<BestStart> = None;
+
+    for (coords, direction) in starts {
+        let component = graph.component_by_state[state::<N>(coords, direction)];
+        let energized_tiles = energized_by_component[&component];
+
+        if best
+            .as_ref()
+            .is_none_or(|best| energized_tiles > best.energized_tiles)
+        {
+            best = Some(BestStart {
+                coords,
+                direction,
+                energized_tiles,
+            });
+        }
+    }
+
+    best.expect("contraption has no edges")
+}
+
+fn state<const N: usize>((x, y): (usize, usize), direction: Direction) -> usize {
+    (y * N + x) * 4 + direction.index()
+}
+
+// Iterative Tarjan, so large contraptions can't overflow the stack. Returns
+// the component of every node and the number of components.
+fn strongly_connected_components(successors: &[Vec<usize>]) -> (Vec<usize>, usize) {
+    let node_count = successors.len();
+
+    let mut index = vec![usize::MAX; node_count];
+    let mut lowlink = vec![0; node_count];
+    let mut on_stack = vec![false; node_count];
+    let mut stack = vec![];
+    let mut component = vec![usize::MAX; node_count];
+    let mut next_index = 0;
+    let mut component_count = 0;
+
+    for root in 0..node_count {
+        if index[root] != usize::MAX {
+            continue;
+        }
+
+        index[root] = next_index;
+        lowlink[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        let mut call_stack = vec![(root, 0)];
+
+        while let Some(&(node, edge)) = call_stack.last() {
+            if edge < successors[node].len() {
+                call_stack.last_mut().unwrap().1 += 1;
+
+                let next = successors[node][edge];
+                if index[next] == usize::MAX {
+                    index[next] = next_index;
+                    lowlink[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    call_stack.push((next, 0));
+                } else if on_stack[next] {
+                    lowlink[node] = lowlink[node].min(index[next]);
+                }
+            } else {
+                call_stack.pop();
+
+                if let Some(&(parent, _)) = call_stack.last() {
+                    lowlink[parent] = lowlink[parent].min(lowlink[node]);
+                }
+
+                if lowlink[node] == index[node] {
+                    loop {
+                        let member = stack.pop().unwrap();
+                        on_stack[member] = false;
+                        component[member] = component_count;
+                        if member == node {
+                            break;
+                        }
+                    }
+                    component_count += 1;
+                }
+            }
+        }
+    }
+
+    (component, component_count)
+}
+
+#[cfg(test)]
+mod sweep_tests {
+    use super::super::{max_energized_by_resimulating, pt1};
+    use super::*;
+
+    const EXAMPLE: &str = ".|...\\....
+|.-.\\.....
+.....|-...
+........|.
+..........
+.........\\
+..../.\\\\..
+.-.-/..|..
+.|....-|.\\
+..//.|....";
+
+    #[test]
+    fn finds_best_start_in_example() {
+        let contraption: Contraption<10> = Contraption::from(EXAMPLE);
+
+        assert_eq!(
+            BestStart {
+                coords: (3, 0),
+                direction: Direction::Down,
+                energized_tiles: 51,
+            },
+            best_start(&contraption, 4)
+        );
+    }
+
+    #[test]
+    fn matches_resimulating_every_start() {
+        let contraption: Contraption<10> = Contraption::from(EXAMPLE);
+        let graph = BeamGraph::new(&contraption);
+
+        assert_eq!(
+            pt1::<10>(EXAMPLE),
+            graph.energized_tiles::<10>((0, 0), Direction::Right)
+        );
+
+        for (coords, direction) in edge_starts::<10>() {
+            let mut contraption: Contraption<10> = Contraption::from(EXAMPLE);
+            contraption.start_beam(coords, direction);
+            assert_eq!(
+                contraption.total_energized_tiles(),
+                graph.energized_tiles::<10>(coords, direction)
+            );
+        }
+
+        for threads in [1, 3, 64] {
+            assert_eq!(
+                max_energized_by_resimulating::<10>(EXAMPLE),
+                best_start(&contraption, threads).energized_tiles
+            );
+        }
+    }
+
+    #[test]
+    fn condenses_loops_into_one_component() {
+        let (component, count) =
+            strongly_connected_components(&[vec![1], vec![2], vec![0, 3], vec![]]);
+
+        assert_eq!(2, count);
+        assert_eq!(component[0], component[1]);
+        assert_eq!(component[1], component[2]);
+        assert_ne!(component[2], component[3]);
+    }
+}
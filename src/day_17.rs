@@ -1,8 +1,8 @@
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
-use std::collections::HashSet;
+use search::{search, Strategy};
 use std::fs;
 
+pub mod search;
+
 pub fn run() {
     let example_input = fs::read_to_string("17_example.txt").unwrap();
     println!("pt1 example: {}", pt1::<13>(&example_input));
@@ -15,213 +15,137 @@ pub fn run() {
 fn pt1<const N: usize>(input: &str) -> usize {
     let heat_loss_map: HeatLossMap<N> = HeatLossMap::from(input);
 
-    let mut heap = BinaryHeap::new();
-    let mut seen = HashSet::new();
+    crucible_route(&heat_loss_map, &Rules::crucible::<N>())
+        .unwrap()
+        .map_or(usize::MAX, |route| route.heat_loss())
+}
 
-    heap.push(PathState {
-        heat_loss: 0,
-        location: (0, 0),
-        direction: Direction::Right,
-        steps_in_direction: 1,
-    });
+fn pt2<const N: usize>(input: &str) -> usize {
+    let heat_loss_map: HeatLossMap<N> = HeatLossMap::from(input);
 
-    heap.push(PathState {
-        heat_loss: 0,
-        location: (0, 0),
-        direction: Direction::Down,
-        steps_in_direction: 1,
-    });
+    crucible_route(&heat_loss_map, &Rules::ultra_crucible::<N>())
+        .unwrap()
+        .map_or(usize::MAX, |route| route.heat_loss())
+}
 
-    let mut minimum_heat_loss = usize::MAX;
+#[derive(Debug, PartialEq)]
+pub enum RulesError {
+    StartOutsideMap { start: (usize, usize) },
+    GoalOutsideMap { goal: (usize, usize) },
+    MinStepsAboveMax { min_steps: usize, max_steps: usize },
+    ZeroMaxSteps,
+}
 
-    while let Some(state) = heap.pop() {
-        if seen.contains(&(state.location, state.direction, state.steps_in_direction)) {
-            continue;
-        };
+#[derive(Debug, Clone, Copy)]
+pub struct Rules {
+    pub min_steps: usize,
+    pub max_steps: usize,
+    pub reversing_allowed: bool,
+    // Whether the crucible must also have moved `min_steps` in a straight line
+    // when it stops at the goal
+    pub min_steps_at_goal: bool,
+    pub start: (usize, usize),
+    pub goal: (usize, usize),
+}
 
-        if state.location == (N - 1, N - 1) {
-            if state.heat_loss < minimum_heat_loss {
-                minimum_heat_loss = state.heat_loss;
-            }
-            continue;
+impl Rules {
+    pub fn crucible<const N: usize>() -> Rules {
+        Rules {
+            min_steps: 1,
+            max_steps: 3,
+            reversing_allowed: false,
+            min_steps_at_goal: false,
+            start: (0, 0),
+            goal: (N - 1, N - 1),
         }
+    }
 
-        let (mut x, mut y) = state.location;
-
-        match state.direction {
-            Direction::Up => {
-                if y == 0 {
-                    continue;
-                } else {
-                    y -= 1
-                }
-            }
-            Direction::Down => {
-                if y == N - 1 {
-                    continue;
-                } else {
-                    y += 1
-                }
-            }
-            Direction::Left => {
-                if x == 0 {
-                    continue;
-                } else {
-                    x -= 1
-                }
-            }
-            Direction::Right => {
-                if x == N - 1 {
-                    continue;
-                } else {
-                    x += 1
-                }
-            }
+    pub fn ultra_crucible<const N: usize>() -> Rules {
+        Rules {
+            min_steps: 4,
+            max_steps: 10,
+            reversing_allowed: false,
+            min_steps_at_goal: true,
+            start: (0, 0),
+            goal: (N - 1, N - 1),
         }
+    }
 
-        let heat_loss = state.heat_loss + heat_loss_map.blocks[y][x];
-
-        for direction in [
-            Direction::Up,
-            Direction::Down,
-            Direction::Left,
-            Direction::Right,
-        ] {
-            if state.direction == direction {
-                if state.steps_in_direction < 3 {
-                    heap.push(PathState {
-                        heat_loss,
-                        location: (x, y),
-                        direction,
-                        steps_in_direction: state.steps_in_direction + 1,
-                    })
-                }
-            } else if state.direction != direction.opposite() {
-                heap.push(PathState {
-                    heat_loss,
-                    location: (x, y),
-                    direction,
-                    steps_in_direction: 1,
-                })
-            }
+    pub fn check<const N: usize>(&self) -> Result<(), RulesError> {
+        let inside = |(x, y): (usize, usize)| x < N && y < N;
+
+        if !inside(self.start) {
+            return Err(RulesError::StartOutsideMap { start: self.start });
         }
-        seen.insert((state.location, state.direction, state.steps_in_direction));
+        if !inside(self.goal) {
+            return Err(RulesError::GoalOutsideMap { goal: self.goal });
+        }
+        if self.max_steps == 0 {
+            return Err(RulesError::ZeroMaxSteps);
+        }
+        if self.min_steps > self.max_steps {
+            return Err(RulesError::MinStepsAboveMax {
+                min_steps: self.min_steps,
+                max_steps: self.max_steps,
+            });
+        }
+        Ok(())
     }
-
-    minimum_heat_loss
 }
 
-fn pt2<const N: usize>(input: &str) -> usize {
-    let heat_loss_map: HeatLossMap<N> = HeatLossMap::from(input);
-
-    let mut heap = BinaryHeap::new();
-    let mut seen = HashSet::new();
-
-    heap.push(PathState {
-        heat_loss: 0,
-        location: (0, 0),
-        direction: Direction::Right,
-        steps_in_direction: 1,
-    });
-
-    heap.push(PathState {
-        heat_loss: 0,
-        location: (0, 0),
-        direction: Direction::Down,
-        steps_in_direction: 1,
-    });
-
-    let mut minimum_heat_loss = usize::MAX;
-
-    while let Some(state) = heap.pop() {
-        if seen.contains(&(state.location, state.direction, state.steps_in_direction)) {
-            continue;
-        };
-
-        if state.location == (N - 1, N - 1) {
-            if state.heat_loss < minimum_heat_loss {
-                minimum_heat_loss = state.heat_loss;
-            }
-            continue;
-        }
+pub fn crucible_route<const N: usize>(
+    heat_loss_map: &HeatLossMap<N>,
+    rules: &Rules,
+) -> Result<Option<Route>, RulesError> {
+    Ok(search(heat_loss_map, rules, Strategy::SegmentsWithHeuristic)?.route)
+}
 
-        let (mut x, mut y) = state.location;
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RouteStep {
+    pub location: (usize, usize),
+    pub direction: Direction,
+    pub heat_loss: usize,
+}
 
-        match state.direction {
-            Direction::Up => {
-                if y == 0 {
-                    continue;
-                } else {
-                    y -= 1
-                }
-            }
-            Direction::Down => {
-                if y == N - 1 {
-                    continue;
-                } else {
-                    y += 1
-                }
-            }
-            Direction::Left => {
-                if x == 0 {
-                    continue;
-                } else {
-                    x -= 1
-                }
-            }
-            Direction::Right => {
-                if x == N - 1 {
-                    continue;
-                } else {
-                    x += 1
-                }
-            }
-        }
+// The blocks entered after leaving the start, each with the direction the
+// crucible moved to enter it and the heat lost so far
+#[derive(Debug, PartialEq)]
+pub struct Route {
+    pub steps: Vec<RouteStep>,
+}
 
-        let heat_loss = state.heat_loss + heat_loss_map.blocks[y][x];
+impl Route {
+    pub fn heat_loss(&self) -> usize {
+        self.steps.last().map_or(0, |step| step.heat_loss)
+    }
 
-        if state.steps_in_direction < 4 {
-            heap.push(PathState {
-                heat_loss,
-                location: (x, y),
-                direction: state.direction,
-                steps_in_direction: state.steps_in_direction + 1,
+    pub fn render<const N: usize>(&self, heat_loss_map: &HeatLossMap<N>) -> String {
+        let mut rows: Vec<Vec<char>> = heat_loss_map
+            .blocks
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|heat_loss| char::from_digit(*heat_loss as u32, 10).unwrap())
+                    .collect()
             })
-        } else {
-            for direction in [
-                Direction::Up,
-                Direction::Down,
-                Direction::Left,
-                Direction::Right,
-            ] {
-                if state.direction == direction {
-                    if state.steps_in_direction < 10 {
-                        heap.push(PathState {
-                            heat_loss,
-                            location: (x, y),
-                            direction,
-                            steps_in_direction: state.steps_in_direction + 1,
-                        })
-                    }
-                } else if state.direction != direction.opposite() {
-                    heap.push(PathState {
-                        heat_loss,
-                        location: (x, y),
-                        direction,
-                        steps_in_direction: 1,
-                    })
-                }
-            }
+            .collect();
+
+        for step in &self.steps {
+            let (x, y) = step.location;
+            rows[y][x] = step.direction.arrow();
         }
 
-        seen.insert((state.location, state.direction, state.steps_in_direction));
+        rows.iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
     }
-
-    minimum_heat_loss
 }
 
-struct HeatLossMap<const N: usize> {
-    blocks: [[usize; N]; N],
+// Rows live on the heap, so maps several times the puzzle's size don't
+// overflow a thread's stack
+pub struct HeatLossMap<const N: usize> {
+    blocks: Vec<[usize; N]>,
 }
 
 impl<const N: usize> From<&str> for HeatLossMap<N> {
@@ -231,16 +155,18 @@ impl<const N: usize> From<&str> for HeatLossMap<N> {
             .map(|l| l.chars().map(|char| char.to_digit(10).unwrap() as usize));
 
         Self {
-            blocks: std::array::from_fn(|_| {
-                let mut line = lines.next().unwrap();
-                std::array::from_fn(|_| line.next().unwrap())
-            }),
+            blocks: (0..N)
+                .map(|_| {
+                    let mut line = lines.next().unwrap();
+                    std::array::from_fn(|_| line.next().unwrap())
+                })
+                .collect(),
         }
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Copy, Clone)]
-enum Direction {
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum Direction {
     Up,
     Down,
     Left,
@@ -256,24 +182,215 @@ impl Direction {
             Self::Right => Self::Left,
         }
     }
-}
 
-#[derive(Eq, PartialEq)]
-struct PathState {
-    heat_loss: usize,
-    location: (usize, usize),
-    direction: Direction,
-    steps_in_direction: usize,
-}
+    fn is_vertical(&self) -> bool {
+        matches!(self, Self::Up | Self::Down)
+    }
 
-impl Ord for PathState {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.heat_loss.cmp(&self.heat_loss)
+    fn step<const N: usize>(&self, (x, y): (usize, usize)) -> Option<(usize, usize)> {
+        match self {
+            Self::Up if y > 0 => Some((x, y - 1)),
+            Self::Down if y < N - 1 => Some((x, y + 1)),
+            Self::Left if x > 0 => Some((x - 1, y)),
+            Self::Right if x < N - 1 => Some((x + 1, y)),
+            _ => None,
+        }
+    }
+
+    fn arrow(&self) -> char {
+        match self {
+            Self::Up => '^',
+            Self::Down => 'v',
+            Self::Left => '<',
+            Self::Right => '>',
+        }
     }
 }
 
-impl PartialOrd for PathState {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+#[cfg(test)]
+mod crucible_route_tests {
+    use super::*;
+
+    const EXAMPLE: &str = "2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533";
+
+    fn assert_valid_route<const N: usize>(
+        heat_loss_map: &HeatLossMap<N>,
+        route: &Route,
+        rules: &Rules,
+    ) {
+        let mut location = rules.start;
+        let mut heat_loss = 0;
+        let mut run = 0;
+        let mut previous_direction = None;
+
+        for step in &route.steps {
+            assert_eq!(Some(step.location), step.direction.step::<N>(location));
+            location = step.location;
+            heat_loss += heat_loss_map.blocks[location.1][location.0];
+            assert_eq!(heat_loss, step.heat_loss);
+
+            if previous_direction == Some(step.direction) {
+                run += 1;
+            } else {
+                assert!(previous_direction.is_none() || run >= rules.min_steps);
+                if !rules.reversing_allowed {
+                    assert_ne!(previous_direction, Some(step.direction.opposite()));
+                }
+                run = 1;
+            }
+            assert!(run <= rules.max_steps);
+            previous_direction = Some(step.direction);
+        }
+
+        assert_eq!(rules.goal, location);
+        if rules.min_steps_at_goal && !route.steps.is_empty() {
+            assert!(run >= rules.min_steps);
+        }
+    }
+
+    #[test]
+    fn matches_searching_cell_by_cell() {
+        let heat_loss_map: HeatLossMap<13> = HeatLossMap::from(EXAMPLE);
+        let cells = |rules: Rules| {
+            let route = search(&heat_loss_map, &rules, Strategy::Cells)
+                .unwrap()
+                .route;
+            route.unwrap().heat_loss()
+        };
+
+        assert_eq!(cells(Rules::crucible::<13>()), pt1::<13>(EXAMPLE));
+        assert_eq!(cells(Rules::ultra_crucible::<13>()), pt2::<13>(EXAMPLE));
+        assert_eq!(102, pt1::<13>(EXAMPLE));
+        assert_eq!(94, pt2::<13>(EXAMPLE));
+    }
+
+    #[test]
+    fn reconstructs_a_legal_route() {
+        let heat_loss_map: HeatLossMap<13> = HeatLossMap::from(EXAMPLE);
+
+        let rules = Rules::crucible::<13>();
+        let route = crucible_route(&heat_loss_map, &rules).unwrap().unwrap();
+        assert_eq!(102, route.heat_loss());
+        assert_valid_route(&heat_loss_map, &route, &rules);
+
+        let rules = Rules::ultra_crucible::<13>();
+        let route = crucible_route(&heat_loss_map, &rules).unwrap().unwrap();
+        assert_eq!(94, route.heat_loss());
+        assert_valid_route(&heat_loss_map, &route, &rules);
+    }
+
+    #[test]
+    fn uses_any_start_and_goal() {
+        let heat_loss_map: HeatLossMap<13> = HeatLossMap::from(EXAMPLE);
+
+        let rules = Rules {
+            start: (12, 12),
+            goal: (0, 0),
+            ..Rules::crucible::<13>()
+        };
+        let route = crucible_route(&heat_loss_map, &rules).unwrap().unwrap();
+        assert_eq!(101, route.heat_loss());
+        assert_valid_route(&heat_loss_map, &route, &rules);
+
+        let rules = Rules {
+            start: (3, 5),
+            goal: (9, 2),
+            ..Rules::crucible::<13>()
+        };
+        let route = crucible_route(&heat_loss_map, &rules).unwrap().unwrap();
+        assert_eq!(46, route.heat_loss());
+        assert_valid_route(&heat_loss_map, &route, &rules);
+
+        let rules = Rules {
+            start: (4, 4),
+            goal: (4, 4),
+            ..Rules::ultra_crucible::<13>()
+        };
+        assert_eq!(
+            Ok(Some(Route { steps: vec![] })),
+            crucible_route(&heat_loss_map, &rules)
+        );
+    }
+
+    #[test]
+    fn can_require_the_minimum_run_at_the_goal() {
+        let heat_loss_map: HeatLossMap<7> = HeatLossMap::from(
+            "1111199
+9999199
+9999199
+9999199
+9999199
+9999199
+9999111",
+        );
+
+        let rules = Rules {
+            min_steps_at_goal: false,
+            ..Rules::ultra_crucible::<7>()
+        };
+        let route = crucible_route(&heat_loss_map, &rules).unwrap().unwrap();
+        assert_eq!(12, route.heat_loss());
+        assert_valid_route(&heat_loss_map, &route, &rules);
+
+        let rules = Rules::ultra_crucible::<7>();
+        let route = crucible_route(&heat_loss_map, &rules).unwrap().unwrap();
+        assert_eq!(68, route.heat_loss());
+        assert_valid_route(&heat_loss_map, &route, &rules);
+    }
+
+    #[test]
+    fn can_allow_reversing() {
+        let heat_loss_map: HeatLossMap<3> = HeatLossMap::from("911\n119\n999");
+
+        let rules = Rules {
+            min_steps: 1,
+            max_steps: 1,
+            reversing_allowed: false,
+            min_steps_at_goal: true,
+            start: (1, 0),
+            goal: (1, 2),
+        };
+        let route = crucible_route(&heat_loss_map, &rules).unwrap().unwrap();
+        assert_eq!(20, route.heat_loss());
+        assert_valid_route(&heat_loss_map, &route, &rules);
+
+        let rules = Rules {
+            reversing_allowed: true,
+            ..rules
+        };
+        let route = crucible_route(&heat_loss_map, &rules).unwrap().unwrap();
+        assert_eq!(12, route.heat_loss());
+        assert_valid_route(&heat_loss_map, &route, &rules);
+    }
+
+    #[test]
+    fn renders_route_with_arrows() {
+        let heat_loss_map: HeatLossMap<3> = HeatLossMap::from("119\n911\n991");
+
+        let route = crucible_route(&heat_loss_map, &Rules::crucible::<3>())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            vec![(1, 1), (2, 2), (3, 3), (4, 4)],
+            route
+                .steps
+                .iter()
+                .map(|step| (step.location.0 + step.location.1, step.heat_loss))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("1>9\n9v>\n99v", route.render(&heat_loss_map));
     }
 }
@@ -0,0 +1,533 @@
+use super::{Direction, HeatLossMap, Route, RouteStep, Rules, RulesError};
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::collections::HashSet;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Strategy {
+    Cells,
+    Segments,
+    SegmentsWithHeuristic,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Search {
+    pub route: Option<Route>,
+    pub nodes_expanded: usize,
+}
+
+pub fn search<const N: usize>(
+    heat_loss_map: &HeatLossMap<N>,
+    rules: &Rules,
+    strategy: Strategy,
+) -> Result<Search, RulesError> {
+    rules.check::<N>()?;
+
+    Ok(match strategy {
+        Strategy::Cells => search_cells(heat_loss_map, rules),
+        Strategy::Segments => search_segments(heat_loss_map, rules, false),
+        Strategy::SegmentsWithHeuristic => search_segments(heat_loss_map, rules, true),
+    })
+}
+
+// Dijkstra over single block moves, keyed on the run so far in each direction
+fn search_cells<const N: usize>(heat_loss_map: &HeatLossMap<N>, rules: &Rules) -> Search {
+    let mut heap = BinaryHeap::new();
+    let mut seen = HashSet::new();
+    let mut nodes_expanded = 0;
+    // Every block entered along a path, pointing back to the block before it
+    let mut route_steps: Vec<(RouteStep, Option<usize>)> = vec![];
+
+    for direction in [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ] {
+        heap.push(PathState {
+            heat_loss: 0,
+            location: rules.start,
+            direction,
+            steps_in_direction: 1,
+            parent: None,
+        });
+    }
+
+    while let Some(state) = heap.pop() {
+        if !seen.insert((state.location, state.direction, state.steps_in_direction)) {
+            continue;
+        }
+        nodes_expanded += 1;
+
+        // A state about to continue straight has only moved one block fewer
+        // than its count, while one about to turn has already finished a run
+        let stopping_allowed = !rules.min_steps_at_goal
+            || state.steps_in_direction == 1
+            || state.steps_in_direction > rules.min_steps;
+
+        if state.location == rules.goal && stopping_allowed {
+            let mut steps = vec![];
+            let mut parent = state.parent;
+            while let Some(index) = parent {
+                let (step, previous) = &route_steps[index];
+                steps.push(*step);
+                parent = *previous;
+            }
+            steps.reverse();
+            return Search {
+                route: Some(Route { steps }),
+                nodes_expanded,
+            };
+        }
+
+        let Some((x, y)) = state.direction.step::<N>(state.location) else {
+            continue;
+        };
+
+        let heat_loss = state.heat_loss + heat_loss_map.blocks[y][x];
+
+        route_steps.push((
+            RouteStep {
+                location: (x, y),
+                direction: state.direction,
+                heat_loss,
+            },
+            state.parent,
+        ));
+        let parent = Some(route_steps.len() - 1);
+
+        for direction in [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ] {
+            let steps_in_direction = if direction == state.direction {
+                state.steps_in_direction + 1
+            } else {
+                1
+            };
+
+            let allowed = if state.steps_in_direction < rules.min_steps {
+                direction == state.direction
+            } else if direction == state.direction {
+                state.steps_in_direction < rules.max_steps
+            } else {
+                rules.reversing_allowed || direction != state.direction.opposite()
+            };
+
+            if allowed {
+                heap.push(PathState {
+                    heat_loss,
+                    location: (x, y),
+                    direction,
+                    steps_in_direction,
+                    parent,
+                });
+            }
+        }
+    }
+
+    Search {
+        route: None,
+        nodes_expanded,
+    }
+}
+
+#[derive(Eq, PartialEq)]
+struct PathState {
+    heat_loss: usize,
+    location: (usize, usize),
+    direction: Direction,
+    steps_in_direction: usize,
+    parent: Option<usize>,
+}
+
+impl Ord for PathState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.heat_loss.cmp(&self.heat_loss)
+    }
+}
+
+impl PartialOrd for PathState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Eq, PartialEq)]
+struct SegmentState {
+    estimate: usize,
+    heat_loss: usize,
+    location: (usize, usize),
+    direction: Option<Direction>,
+    node: Option<usize>,
+}
+
+impl Ord for SegmentState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.cmp(&self.estimate)
+    }
+}
+
+impl PartialOrd for SegmentState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+struct SegmentEnd {
+    location: (usize, usize),
+    direction: Direction,
+    parent: Option<usize>,
+}
+
+// Each move is a whole straight segment of a legal length, so the crucible
+// always turns between moves. Without reversing, which way it came along an
+// axis makes no difference to where it can go next, so states are keyed on
+// the axis alone. The optional A* heuristic is the cheapest heat loss from a
+// block to the goal with no limits on turning, which never overestimates.
+fn search_segments<const N: usize>(
+    heat_loss_map: &HeatLossMap<N>,
+    rules: &Rules,
+    use_heuristic: bool,
+) -> Search {
+    let heat_loss_to_goal = if use_heuristic {
+        heat_loss_to_goal(heat_loss_map, rules.goal)
+    } else {
+        vec![0; N * N]
+    };
+    let heuristic = |(x, y): (usize, usize)| heat_loss_to_goal[y * N + x];
+
+    let heading = |direction: Direction| {
+        if rules.reversing_allowed {
+            direction
+        } else if direction.is_vertical() {
+            Direction::Down
+        } else {
+            Direction::Right
+        }
+    };
+
+    let mut heap = BinaryHeap::new();
+    let mut seen = HashSet::new();
+    let mut segment_ends: Vec<SegmentEnd> = vec![];
+    let mut nodes_expanded = 0;
+
+    heap.push(SegmentState {
+        estimate: heuristic(rules.start),
+        heat_loss: 0,
+        location: rules.start,
+        direction: None,
+        node: None,
+    });
+
+    while let Some(state) = heap.pop() {
+        if !seen.insert((state.location, state.direction.map(heading))) {
+            continue;
+        }
+        nodes_expanded += 1;
+
+        if state.location == rules.goal {
+            return Search {
+                route: Some(route(heat_loss_map, rules, &segment_ends, state.node)),
+                nodes_expanded,
+            };
+        }
+
+        for direction in [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ] {
+            if let Some(previous) = state.direction {
+                let reversing = direction == previous.opposite();
+                if direction == previous || (reversing && !rules.reversing_allowed) {
+                    continue;
+                }
+            }
+
+            let mut location = state.location;
+            let mut heat_loss = state.heat_loss;
+
+            for steps in 1..=rules.max_steps {
+                let Some((x, y)) = direction.step::<N>(location) else {
+                    break;
+                };
+                location = (x, y);
+                heat_loss += heat_loss_map.blocks[y][x];
+
+                let stopping_at_goal = location == rules.goal && !rules.min_steps_at_goal;
+
+                if steps >= rules.min_steps || stopping_at_goal {
+                    segment_ends.push(SegmentEnd {
+                        location,
+                        direction,
+                        parent: state.node,
+                    });
+                    heap.push(SegmentState {
+                        estimate: heat_loss + heuristic(location),
+                        heat_loss,
+                        location,
+                        direction: Some(direction),
+                        node: Some(segment_ends.len() - 1),
+                    });
+                }
+            }
+        }
+    }
+
+    Search {
+        route: None,
+        nodes_expanded,
+    }
+}
+
+// Dijkstra outwards from the goal, where stepping from a block onto its
+// neighbour costs the heat lost entering the neighbour
+fn heat_loss_to_goal<const N: usize>(
+    heat_loss_map: &HeatLossMap<N>,
+    goal: (usize, usize),
+) -> Vec<usize> {
+    let mut heat_loss_to_goal = vec![usize::MAX; N * N];
+    let mut heap = BinaryHeap::new();
+
+    heat_loss_to_goal[goal.1 * N + goal.0] = 0;
+    heap.push(Reverse((0, goal)));
+
+    while let Some(Reverse((heat_loss, (x, y)))) = heap.pop() {
+        if heat_loss > heat_loss_to_goal[y * N + x] {
+            continue;
+        }
+
+        let heat_loss = heat_loss + heat_loss_map.blocks[y][x];
+
+        for direction in [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ] {
+            if let Some((next_x, next_y)) = direction.step::<N>((x, y)) {
+                if heat_loss < heat_loss_to_goal[next_y * N + next_x] {
+                    heat_loss_to_goal[next_y * N + next_x] = heat_loss;
+                    heap.push(Reverse((heat_loss, (next_x, next_y))));
+                }
+            }
+        }
+    }
+
+    heat_loss_to_goal
+}
+
+fn route<const N: usize>(
+    heat_loss_map: &HeatLossMap<N>,
+    rules: &Rules,
+    segment_ends: &[SegmentEnd],
+    node: Option<usize>,
+) -> Route {
+    let mut segments = vec![];
+    let mut node = node;
+    while let Some(index) = node {
+        segments.push(&segment_ends[index]);
+        node = segment_ends[index].parent;
+    }
+    segments.reverse();
+
+    let mut steps = vec![];
+    let mut location = rules.start;
+    let mut heat_loss = 0;
+
+    for segment in segments {
+        while location != segment.location {
+            let (x, y) = segment.direction.step::<N>(location).unwrap();
+            location = (x, y);
+            heat_loss += heat_loss_map.blocks[y][x];
+            steps.push(RouteStep {
+                location,
+                direction: segment.direction,
+                heat_loss,
+            });
+        }
+    }
+
+    Route { steps }
+}
+
+#[cfg(test)]
+mod search_tests {
+    use super::*;
+
+    const STRATEGIES: [Strategy; 3] = [
+        Strategy::Cells,
+        Strategy::Segments,
+        Strategy::SegmentsWithHeuristic,
+    ];
+
+    const EXAMPLE: &str = "2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533";
+
+    // A deterministic pseudo-random map, large enough to be several times the
+    // size of the puzzle's
+    fn large_map<const N: usize>() -> String {
+        let mut seed: u64 = 17;
+        (0..N)
+            .map(|_| {
+                (0..N)
+                    .map(|_| {
+                        seed = seed
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        char::from_digit((seed >> 33) as u32 % 9 + 1, 10).unwrap()
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn strategies_agree_on_the_example() {
+        let heat_loss_map: HeatLossMap<13> = HeatLossMap::from(EXAMPLE);
+
+        for rules in [Rules::crucible::<13>(), Rules::ultra_crucible::<13>()] {
+            let heat_losses: Vec<_> = STRATEGIES
+                .iter()
+                .map(|strategy| {
+                    search(&heat_loss_map, &rules, *strategy)
+                        .unwrap()
+                        .route
+                        .unwrap()
+                        .heat_loss()
+                })
+                .collect();
+            assert_eq!(vec![heat_losses[0]; 3], heat_losses);
+        }
+    }
+
+    #[test]
+    fn strategies_agree_with_unusual_rules() {
+        let heat_loss_map: HeatLossMap<3> = HeatLossMap::from("911\n119\n999");
+
+        for reversing_allowed in [false, true] {
+            let rules = Rules {
+                min_steps: 1,
+                max_steps: 1,
+                reversing_allowed,
+                min_steps_at_goal: true,
+                start: (1, 0),
+                goal: (1, 2),
+            };
+
+            let cells = search(&heat_loss_map, &rules, Strategy::Cells).unwrap();
+            for strategy in [Strategy::Segments, Strategy::SegmentsWithHeuristic] {
+                let segments = search(&heat_loss_map, &rules, strategy).unwrap();
+                assert_eq!(
+                    cells.route.as_ref().map(|route| route.heat_loss()),
+                    segments.route.as_ref().map(|route| route.heat_loss())
+                );
+            }
+        }
+
+        let rules = Rules {
+            min_steps: 3,
+            goal: (1, 1),
+            ..Rules::crucible::<3>()
+        };
+        for strategy in STRATEGIES {
+            assert_eq!(
+                None,
+                search(&heat_loss_map, &rules, strategy).unwrap().route
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_impossible_rules() {
+        let heat_loss_map: HeatLossMap<3> = HeatLossMap::from("911\n119\n999");
+
+        let rules = Rules {
+            start: (3, 0),
+            ..Rules::crucible::<3>()
+        };
+        assert_eq!(
+            Err(RulesError::StartOutsideMap { start: (3, 0) }),
+            search(&heat_loss_map, &rules, Strategy::Cells)
+        );
+
+        let rules = Rules {
+            goal: (0, 7),
+            ..Rules::crucible::<3>()
+        };
+        assert_eq!(
+            Err(RulesError::GoalOutsideMap { goal: (0, 7) }),
+            search(&heat_loss_map, &rules, Strategy::Segments)
+        );
+
+        let rules = Rules {
+            min_steps: 4,
+            ..Rules::crucible::<3>()
+        };
+        assert_eq!(
+            Err(RulesError::MinStepsAboveMax {
+                min_steps: 4,
+                max_steps: 3
+            }),
+            search(&heat_loss_map, &rules, Strategy::SegmentsWithHeuristic)
+        );
+
+        let rules = Rules {
+            min_steps: 0,
+            max_steps: 0,
+            ..Rules::crucible::<3>()
+        };
+        for strategy in [Strategy::Cells, Strategy::Segments] {
+            assert_eq!(
+                Err(RulesError::ZeroMaxSteps),
+                search(&heat_loss_map, &rules, strategy)
+            );
+        }
+    }
+
+    fn assert_jumping_segments_expands_fewer_nodes<const N: usize>() {
+        let heat_loss_map: HeatLossMap<N> = HeatLossMap::from(large_map::<N>().as_str());
+
+        for rules in [Rules::crucible::<N>(), Rules::ultra_crucible::<N>()] {
+            let cells = search(&heat_loss_map, &rules, Strategy::Cells).unwrap();
+            let segments = search(&heat_loss_map, &rules, Strategy::Segments).unwrap();
+            let a_star = search(&heat_loss_map, &rules, Strategy::SegmentsWithHeuristic).unwrap();
+
+            let heat_loss = cells.route.unwrap().heat_loss();
+            assert_eq!(heat_loss, segments.route.unwrap().heat_loss());
+            assert_eq!(heat_loss, a_star.route.unwrap().heat_loss());
+
+            assert!(segments.nodes_expanded < cells.nodes_expanded);
+            assert!(a_star.nodes_expanded < segments.nodes_expanded);
+        }
+    }
+
+    #[test]
+    fn jumping_segments_expands_fewer_nodes_on_a_small_map() {
+        assert_jumping_segments_expands_fewer_nodes::<60>();
+    }
+
+    // Three times the puzzle's 141 by 141 map. Too slow for a debug build, so
+    // run it with `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn jumping_segments_expands_fewer_nodes_on_a_large_map() {
+        assert_jumping_segments_expands_fewer_nodes::<423>();
+    }
+}
@@ -0,0 +1,336 @@
+// Line numbers count from 1, like an editor shows them
+#[derive(Debug, PartialEq)]
+pub enum DigPlanError {
+    Empty,
+    InvalidInstruction { line: usize, text: String },
+    Overflow { line: usize },
+    NotClosed { end: (i128, i128) },
+    SelfIntersecting { edges: (usize, usize) },
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    fn offset(&self) -> (i128, i128) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Instruction {
+    pub direction: Direction,
+    pub meters: i128,
+    pub colour: Option<[u8; 3]>,
+}
+
+// Turns one line of the dig plan into an instruction, or None if the line
+// can't be read that way
+pub type Decoder = fn(&str) -> Option<Instruction>;
+
+// Reads "R 6 (#70c710)" as 6 meters right
+pub fn decode_direction(line: &str) -> Option<Instruction> {
+    let mut parts = line.split_whitespace();
+
+    let direction = match parts.next()? {
+        "R" => Direction::Right,
+        "L" => Direction::Left,
+        "U" => Direction::Up,
+        "D" => Direction::Down,
+        _ => return None,
+    };
+    let meters = parts.next()?.parse().ok()?;
+    let colour = parts.next().and_then(colour_hex).and_then(parse_colour);
+
+    Some(Instruction {
+        direction,
+        meters,
+        colour,
+    })
+}
+
+// Reads "R 6 (#70c710)" as 0x70c71 meters right, taking the distance from the
+// first five hex digits and the direction from the last
+pub fn decode_colour(line: &str) -> Option<Instruction> {
+    let hex = colour_hex(line.split_whitespace().nth(2)?)?;
+    let colour = parse_colour(hex);
+
+    let meters = i128::from_str_radix(&hex[..5], 16).ok()?;
+    let direction = match &hex[5..] {
+        "0" => Direction::Right,
+        "1" => Direction::Down,
+        "2" => Direction::Left,
+        "3" => Direction::Up,
+        _ => return None,
+    };
+
+    Some(Instruction {
+        direction,
+        meters,
+        colour,
+    })
+}
+
+fn colour_hex(part: &str) -> Option<&str> {
+    let hex = part.strip_prefix("(#")?.strip_suffix(")")?;
+
+    if hex.len() == 6 && hex.chars().all(|char| char.is_ascii_hexdigit()) {
+        Some(hex)
+    } else {
+        None
+    }
+}
+
+fn parse_colour(hex: &str) -> Option<[u8; 3]> {
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+// The trench as a closed polygon through the centres of its corner cells
+#[derive(Debug, PartialEq)]
+pub struct DigPlan {
+    pub vertices: Vec<(i128, i128)>,
+    // The colour of the edge from each vertex to the next
+    pub edge_colours: Vec<Option<[u8; 3]>>,
+    boundary: i128,
+    double_area: i128,
+}
+
+impl DigPlan {
+    pub fn parse(input: &str, decoder: Decoder) -> Result<DigPlan, DigPlanError> {
+        let mut vertices = vec![(0, 0)];
+        let mut edge_colours = vec![];
+        let mut boundary: i128 = 0;
+        let mut double_area: i128 = 0;
+        let (mut x, mut y): (i128, i128) = (0, 0);
+
+        for (index, text) in input.lines().enumerate() {
+            let line = index + 1;
+            let invalid = || DigPlanError::InvalidInstruction {
+                line,
+                text: text.to_string(),
+            };
+            let overflow = || DigPlanError::Overflow { line };
+
+            let instruction = decoder(text).ok_or_else(invalid)?;
+            if instruction.meters <= 0 {
+                return Err(invalid());
+            }
+
+            let (dx, dy) = instruction.direction.offset();
+            let next_x = dx
+                .checked_mul(instruction.meters)
+                .and_then(|dx| x.checked_add(dx))
+                .ok_or_else(overflow)?;
+            let next_y = dy
+                .checked_mul(instruction.meters)
+                .and_then(|dy| y.checked_add(dy))
+                .ok_or_else(overflow)?;
+
+            // Shoelace formula
+            double_area = (next_x - x)
+                .checked_mul(next_y.checked_add(y).ok_or_else(overflow)?)
+                .and_then(|term| double_area.checked_add(term))
+                .ok_or_else(overflow)?;
+            boundary = boundary
+                .checked_add(instruction.meters)
+                .ok_or_else(overflow)?;
+
+            (x, y) = (next_x, next_y);
+            vertices.push((x, y));
+            edge_colours.push(instruction.colour);
+        }
+
+        if vertices.len() == 1 {
+            return Err(DigPlanError::Empty);
+        }
+        if (x, y) != (0, 0) {
+            return Err(DigPlanError::NotClosed { end: (x, y) });
+        }
+        vertices.pop();
+
+        if let Some(edges) = intersecting_edges(&vertices) {
+            return Err(DigPlanError::SelfIntersecting { edges });
+        }
+
+        Ok(DigPlan {
+            vertices,
+            edge_colours,
+            boundary,
+            double_area: double_area.abs(),
+        })
+    }
+
+    pub fn boundary_cells(&self) -> i128 {
+        self.boundary
+    }
+
+    // Pick's theorem: area = interior + boundary / 2 - 1
+    pub fn interior_cells(&self) -> i128 {
+        (self.double_area - self.boundary) / 2 + 1
+    }
+
+    pub fn total_cells(&self) -> i128 {
+        self.interior_cells() + self.boundary_cells()
+    }
+}
+
+// Every edge may only touch its neighbours, and then only at their shared
+// corner. Edge i runs from vertex i to vertex i + 1.
+fn intersecting_edges(vertices: &[(i128, i128)]) -> Option<(usize, usize)> {
+    let edge_count = vertices.len();
+    let edge = |i: usize| (vertices[i], vertices[(i + 1) % edge_count]);
+
+    for i in 0..edge_count {
+        for j in i + 1..edge_count {
+            let (a, b) = edge(i);
+            let (c, d) = edge(j);
+
+            let adjacent = j == i + 1 || (i == 0 && j == edge_count - 1);
+            let intersecting = if adjacent {
+                // Neighbours only overlap when the second doubles back
+                let (first, second) = if j == i + 1 { (a, d) } else { (c, b) };
+                let shared = if j == i + 1 { b } else { a };
+                doubles_back(first, shared, second)
+            } else {
+                overlaps(a, b, c, d)
+            };
+
+            if intersecting {
+                return Some((i, j));
+            }
+        }
+    }
+
+    None
+}
+
+fn doubles_back(from: (i128, i128), corner: (i128, i128), to: (i128, i128)) -> bool {
+    let incoming = ((corner.0 - from.0).signum(), (corner.1 - from.1).signum());
+    let outgoing = ((to.0 - corner.0).signum(), (to.1 - corner.1).signum());
+    incoming == (-outgoing.0, -outgoing.1)
+}
+
+fn overlaps(a: (i128, i128), b: (i128, i128), c: (i128, i128), d: (i128, i128)) -> bool {
+    a.0.min(b.0) <= c.0.max(d.0)
+        && c.0.min(d.0) <= a.0.max(b.0)
+        && a.1.min(b.1) <= c.1.max(d.1)
+        && c.1.min(d.1) <= a.1.max(b.1)
+}
+
+#[cfg(test)]
+mod dig_plan_tests {
+    use super::*;
+
+    const EXAMPLE: &str = "R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)";
+
+    #[test]
+    fn counts_cells_for_both_decoders() {
+        let plan = DigPlan::parse(EXAMPLE, decode_direction).unwrap();
+        assert_eq!(38, plan.boundary_cells());
+        assert_eq!(24, plan.interior_cells());
+        assert_eq!(62, plan.total_cells());
+
+        let plan = DigPlan::parse(EXAMPLE, decode_colour).unwrap();
+        assert_eq!(952408144115, plan.total_cells());
+    }
+
+    #[test]
+    fn decodes_instructions() {
+        assert_eq!(
+            Some(Instruction {
+                direction: Direction::Right,
+                meters: 6,
+                colour: Some([0x70, 0xc7, 0x10]),
+            }),
+            decode_direction("R 6 (#70c710)")
+        );
+        assert_eq!(
+            Some(Instruction {
+                direction: Direction::Right,
+                meters: 461937,
+                colour: Some([0x70, 0xc7, 0x10]),
+            }),
+            decode_colour("R 6 (#70c710)")
+        );
+        assert_eq!(None, decode_colour("R 6 (#70c714)"));
+        assert_eq!(None, decode_direction("X 6 (#70c710)"));
+        assert_eq!(None, decode_direction("U 2").unwrap().colour);
+    }
+
+    #[test]
+    fn rejects_invalid_plans() {
+        assert_eq!(
+            Err(DigPlanError::Empty),
+            DigPlan::parse("", decode_direction)
+        );
+        assert_eq!(
+            Err(DigPlanError::InvalidInstruction {
+                line: 2,
+                text: "D five".to_string()
+            }),
+            DigPlan::parse("R 5\nD five", decode_direction)
+        );
+        assert_eq!(
+            Err(DigPlanError::NotClosed { end: (2, 1) }),
+            DigPlan::parse("R 2\nD 2\nU 1", decode_direction)
+        );
+        assert_eq!(
+            Err(DigPlanError::Overflow { line: 2 }),
+            DigPlan::parse(
+                "R 170141183460469231731687303715884105727\nR 1",
+                decode_direction
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_self_intersecting_plans() {
+        let crossing = "R 4\nD 2\nL 2\nU 4\nL 2\nD 2";
+        assert_eq!(
+            Err(DigPlanError::SelfIntersecting { edges: (0, 3) }),
+            DigPlan::parse(crossing, decode_direction)
+        );
+
+        let touching = "R 4\nD 2\nL 2\nU 2\nL 2";
+        assert_eq!(
+            Err(DigPlanError::SelfIntersecting { edges: (0, 3) }),
+            DigPlan::parse(touching, decode_direction)
+        );
+
+        let doubling_back = "R 4\nL 2\nD 2\nL 2\nU 2";
+        assert_eq!(
+            Err(DigPlanError::SelfIntersecting { edges: (0, 1) }),
+            DigPlan::parse(doubling_back, decode_direction)
+        );
+
+        let there_and_back = "R 2\nL 2";
+        assert_eq!(
+            Err(DigPlanError::SelfIntersecting { edges: (0, 1) }),
+            DigPlan::parse(there_and_back, decode_direction)
+        );
+    }
+}
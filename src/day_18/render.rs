@@ -0,0 +1,214 @@
+use super::dig_plan::DigPlan;
+use std::fs;
+use std::io;
+
+const GROUND: [u8; 3] = [0xff, 0xff, 0xff];
+const INTERIOR: [u8; 3] = [0x40, 0x40, 0x40];
+const UNCOLOURED_TRENCH: [u8; 3] = [0x00, 0x00, 0x00];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Pixel {
+    Ground,
+    Interior,
+    Trench([u8; 3]),
+}
+
+// Each pixel covers a `scale` by `scale` block of cells, starting from the
+// top left corner of the plan's bounding box
+#[derive(Debug)]
+pub struct Raster {
+    pub scale: i128,
+    pub pixels: Vec<Vec<Pixel>>,
+}
+
+// Scales the plan down until neither side is more than `max_size` pixels. A
+// pixel is trench if any edge crosses its block, otherwise it takes whether
+// the cell at the centre of its block is inside the lagoon.
+pub fn rasterise(plan: &DigPlan, max_size: usize) -> Raster {
+    let min_x = plan.vertices.iter().map(|(x, _)| *x).min().unwrap();
+    let max_x = plan.vertices.iter().map(|(x, _)| *x).max().unwrap();
+    let min_y = plan.vertices.iter().map(|(_, y)| *y).min().unwrap();
+    let max_y = plan.vertices.iter().map(|(_, y)| *y).max().unwrap();
+
+    let cells_wide = max_x - min_x + 1;
+    let cells_high = max_y - min_y + 1;
+    let max_size = max_size.max(1) as i128;
+    let scale = ((cells_wide + max_size - 1) / max_size)
+        .max((cells_high + max_size - 1) / max_size)
+        .max(1);
+
+    let width = ((cells_wide + scale - 1) / scale) as usize;
+    let height = ((cells_high + scale - 1) / scale) as usize;
+
+    let sample =
+        |pixel: usize, min: i128, max: i128| (min + pixel as i128 * scale + scale / 2).min(max);
+
+    let edge_count = plan.vertices.len();
+    let edges: Vec<_> = (0..edge_count)
+        .map(|i| (plan.vertices[i], plan.vertices[(i + 1) % edge_count]))
+        .collect();
+
+    let mut pixels = vec![vec![Pixel::Ground; width]; height];
+
+    for (py, row) in pixels.iter_mut().enumerate() {
+        let y = sample(py, min_y, max_y);
+
+        // Even-odd rule along the row, counting vertical edges over the
+        // half-open range [top, bottom) so corners are only counted once
+        let mut crossings: Vec<_> = edges
+            .iter()
+            .filter(|((x1, y1), (x2, y2))| x1 == x2 && *y1.min(y2) <= y && y < *y1.max(y2))
+            .map(|((x, _), _)| *x)
+            .collect();
+        crossings.sort();
+
+        for (px, pixel) in row.iter_mut().enumerate() {
+            let x = sample(px, min_x, max_x);
+            let crossed = crossings
+                .iter()
+                .take_while(|crossing| **crossing < x)
+                .count();
+            if crossed % 2 == 1 {
+                *pixel = Pixel::Interior;
+            }
+        }
+    }
+
+    for (((x1, y1), (x2, y2)), colour) in edges.iter().zip(&plan.edge_colours) {
+        let colour = colour.unwrap_or(UNCOLOURED_TRENCH);
+        let to_pixel = |value: i128, min: i128| ((value - min) / scale) as usize;
+
+        let rows = to_pixel(*y1.min(y2), min_y)..=to_pixel(*y1.max(y2), min_y);
+        let columns = to_pixel(*x1.min(x2), min_x)..=to_pixel(*x1.max(x2), min_x);
+
+        for row in &mut pixels[rows] {
+            for pixel in &mut row[columns.clone()] {
+                *pixel = Pixel::Trench(colour);
+            }
+        }
+    }
+
+    Raster { scale, pixels }
+}
+
+impl Raster {
+    pub fn to_ascii(&self) -> String {
+        self.pixels
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|pixel| match pixel {
+                        Pixel::Ground => '.',
+                        Pixel::Interior | Pixel::Trench(_) => '#',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // A binary PPM, which most image viewers and converters can open
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let height = self.pixels.len();
+        let width = self.pixels.first().map_or(0, |row| row.len());
+
+        let mut ppm = format!("P6\n{width} {height}\n255\n").into_bytes();
+        for pixel in self.pixels.iter().flatten() {
+            let rgb = match pixel {
+                Pixel::Ground => GROUND,
+                Pixel::Interior => INTERIOR,
+                Pixel::Trench(colour) => *colour,
+            };
+            ppm.extend_from_slice(&rgb);
+        }
+        ppm
+    }
+
+    pub fn export_ppm(&self, file_path: &str) -> io::Result<()> {
+        fs::write(file_path, self.to_ppm())
+    }
+}
+
+#[cfg(test)]
+mod rasterise_tests {
+    use super::super::dig_plan::{decode_colour, decode_direction};
+    use super::*;
+
+    const EXAMPLE: &str = "R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)";
+
+    #[test]
+    fn draws_the_filled_lagoon_like_the_puzzle() {
+        let plan = DigPlan::parse(EXAMPLE, decode_direction).unwrap();
+        let raster = rasterise(&plan, 100);
+
+        let expected = "#######
+#######
+#######
+..#####
+..#####
+#######
+#####..
+#######
+.######
+.######";
+
+        assert_eq!(1, raster.scale);
+        assert_eq!(expected, raster.to_ascii());
+
+        let interior = raster.pixels.iter().flatten();
+        let interior = interior.filter(|pixel| **pixel == Pixel::Interior).count();
+        assert_eq!(plan.interior_cells(), interior as i128);
+    }
+
+    #[test]
+    fn colours_trench_edges() {
+        let plan = DigPlan::parse(EXAMPLE, decode_direction).unwrap();
+        let raster = rasterise(&plan, 100);
+
+        assert_eq!(Pixel::Trench([0x70, 0xc7, 0x10]), raster.pixels[0][3]);
+        assert_eq!(Pixel::Trench([0x0d, 0xc5, 0x71]), raster.pixels[3][6]);
+        assert_eq!(Pixel::Interior, raster.pixels[1][1]);
+        assert_eq!(Pixel::Ground, raster.pixels[3][0]);
+    }
+
+    #[test]
+    fn writes_a_ppm_image() {
+        let plan = DigPlan::parse(EXAMPLE, decode_direction).unwrap();
+        let ppm = rasterise(&plan, 100).to_ppm();
+
+        let header = b"P6\n7 10\n255\n";
+        assert_eq!(header, &ppm[..header.len()]);
+        assert_eq!(header.len() + 7 * 10 * 3, ppm.len());
+        assert_eq!(
+            &[0x70, 0xc7, 0x10],
+            &ppm[header.len() + 3 * 3..header.len() + 4 * 3]
+        );
+    }
+
+    #[test]
+    fn scales_down_huge_plans() {
+        let plan = DigPlan::parse(EXAMPLE, decode_colour).unwrap();
+        let raster = rasterise(&plan, 40);
+
+        assert!(raster.scale > 1);
+        assert!(raster.pixels.len() <= 40);
+        assert!(raster.pixels.iter().all(|row| row.len() <= 40));
+
+        let pixels: Vec<_> = raster.pixels.iter().flatten().collect();
+        assert!(pixels.contains(&&Pixel::Interior));
+        assert!(pixels.contains(&&Pixel::Trench([0x70, 0xc7, 0x10])));
+    }
+}
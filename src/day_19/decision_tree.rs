@@ -0,0 +1,427 @@
+use super::{parse_workflows, Part, Rule, Workflow};
+use std::collections::HashMap;
+
+pub(super) const MIN_RATING: u64 = 1;
+pub(super) const MAX_RATING: u64 = 4000;
+
+#[derive(Debug, PartialEq)]
+pub enum WorkflowError {
+    MissingStart,
+    DanglingName {
+        workflow: String,
+        destination: String,
+    },
+    NoFallback {
+        workflow: String,
+    },
+    Cycle(Vec<String>),
+}
+
+// Every workflow inlined into one tree. Each branch asks whether one of the
+// part's ratings is below a threshold, so conditions written as `x>10` become
+// "x below 11" with the two outcomes swapped.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Node {
+    Accept,
+    Reject,
+    Branch {
+        category: usize,
+        threshold: u64,
+        below: Box<Node>,
+        at_or_above: Box<Node>,
+    },
+}
+
+#[derive(Debug)]
+pub struct DecisionTree {
+    pub root: Node,
+}
+
+impl DecisionTree {
+    pub fn load(workflows_str: &str) -> Result<DecisionTree, WorkflowError> {
+        let workflows = parse_workflows(workflows_str);
+        validate(&workflows)?;
+
+        let ranges = [[MIN_RATING, MAX_RATING]; 4];
+        let root = compile(&workflows, "in", 0, ranges)?;
+
+        Ok(DecisionTree { root })
+    }
+
+    pub fn accepts(&self, part: &Part) -> bool {
+        let ratings = [part.x, part.m, part.a, part.s];
+        let mut node = &self.root;
+
+        loop {
+            match node {
+                Node::Accept => return true,
+                Node::Reject => return false,
+                Node::Branch {
+                    category,
+                    threshold,
+                    below,
+                    at_or_above,
+                } => {
+                    node = if ratings[*category] < *threshold {
+                        below
+                    } else {
+                        at_or_above
+                    };
+                }
+            }
+        }
+    }
+
+    pub fn accepted_combinations(&self) -> u64 {
+        accepted_combinations(&self.root, [[MIN_RATING, MAX_RATING]; 4])
+    }
+
+    pub fn branch_count(&self) -> usize {
+        branch_count(&self.root)
+    }
+}
+
+pub(super) fn category_index(part_property: char) -> usize {
+    match part_property {
+        'x' => 0,
+        'm' => 1,
+        'a' => 2,
+        's' => 3,
+        _ => panic!("invalid part property"),
+    }
+}
+
+// Inlines the workflow from `rule_index` onwards, given the inclusive range
+// each rating can still be in. Rules that can't match the ranges are dropped,
+// as are rules that send both outcomes to the same place.
+fn compile(
+    workflows: &HashMap<&str, Workflow>,
+    workflow_name: &str,
+    rule_index: usize,
+    ranges: [[u64; 2]; 4],
+) -> Result<Node, WorkflowError> {
+    match workflow_name {
+        "A" => return Ok(Node::Accept),
+        "R" => return Ok(Node::Reject),
+        _ => (),
+    }
+
+    let workflow = &workflows[workflow_name];
+
+    let Some(rule) = workflow.rules.get(rule_index) else {
+        return Err(WorkflowError::NoFallback {
+            workflow: workflow_name.to_string(),
+        });
+    };
+
+    let Some(condition) = &rule.condition else {
+        return compile(workflows, rule.destination_workflow, 0, ranges);
+    };
+
+    let category = category_index(condition.part_property);
+    let threshold = match condition.operator {
+        '<' => condition.value,
+        '>' => condition.value + 1,
+        _ => panic!("invalid condition operator"),
+    };
+
+    let compile_matching = |ranges| compile(workflows, rule.destination_workflow, 0, ranges);
+    let compile_not_matching = |ranges| compile(workflows, workflow_name, rule_index + 1, ranges);
+
+    let (matching, not_matching) = match split(ranges, rule) {
+        (Some(matching), None) => return compile_matching(matching),
+        (None, Some(not_matching)) => return compile_not_matching(not_matching),
+        (Some(matching), Some(not_matching)) => (
+            compile_matching(matching)?,
+            compile_not_matching(not_matching)?,
+        ),
+        (None, None) => unreachable!("ranges are never empty"),
+    };
+
+    let (below, at_or_above) = if condition.operator == '<' {
+        (matching, not_matching)
+    } else {
+        (not_matching, matching)
+    };
+
+    if below == at_or_above {
+        Ok(below)
+    } else {
+        Ok(Node::Branch {
+            category,
+            threshold,
+            below: Box::new(below),
+            at_or_above: Box::new(at_or_above),
+        })
+    }
+}
+
+pub(super) type Split = (Option<[[u64; 2]; 4]>, Option<[[u64; 2]; 4]>);
+
+// The parts of the ranges that do and don't match the rule, if any
+pub(super) fn split(ranges: [[u64; 2]; 4], rule: &Rule) -> Split {
+    let Some(condition) = &rule.condition else {
+        return (Some(ranges), None);
+    };
+
+    let category = category_index(condition.part_property);
+    let [low, high] = ranges[category];
+
+    let (matching, not_matching) = match condition.operator {
+        '<' => (
+            [low, high.min(condition.value.saturating_sub(1))],
+            [low.max(condition.value), high],
+        ),
+        '>' => (
+            [low.max(condition.value + 1), high],
+            [low, high.min(condition.value)],
+        ),
+        _ => panic!("invalid condition operator"),
+    };
+
+    let narrowed = |[low, high]: [u64; 2]| {
+        let mut ranges = ranges;
+        ranges[category] = [low, high];
+        (low <= high).then_some(ranges)
+    };
+
+    (narrowed(matching), narrowed(not_matching))
+}
+
+pub(super) fn validate(workflows: &HashMap<&str, Workflow>) -> Result<(), WorkflowError> {
+    if !workflows.contains_key("in") {
+        return Err(WorkflowError::MissingStart);
+    }
+
+    let mut names: Vec<_> = workflows.keys().copied().collect();
+    names.sort();
+
+    for name in &names {
+        for rule in &workflows[name].rules {
+            let destination = rule.destination_workflow;
+            if !matches!(destination, "A" | "R") && !workflows.contains_key(destination) {
+                return Err(WorkflowError::DanglingName {
+                    workflow: name.to_string(),
+                    destination: destination.to_string(),
+                });
+            }
+        }
+    }
+
+    let mut finished = vec![];
+    for name in &names {
+        let mut path = vec![];
+        find_cycle(workflows, name, &mut path, &mut finished)?;
+    }
+
+    Ok(())
+}
+
+fn find_cycle<'a>(
+    workflows: &HashMap<&'a str, Workflow<'a>>,
+    name: &'a str,
+    path: &mut Vec<&'a str>,
+    finished: &mut Vec<&'a str>,
+) -> Result<(), WorkflowError> {
+    if matches!(name, "A" | "R") || finished.contains(&name) {
+        return Ok(());
+    }
+
+    if let Some(start) = path.iter().position(|visiting| *visiting == name) {
+        let mut cycle: Vec<_> = path[start..].iter().map(|name| name.to_string()).collect();
+        cycle.push(name.to_string());
+        return Err(WorkflowError::Cycle(cycle));
+    }
+
+    path.push(name);
+    for rule in &workflows[name].rules {
+        find_cycle(workflows, rule.destination_workflow, path, finished)?;
+    }
+    path.pop();
+
+    finished.push(name);
+    Ok(())
+}
+
+fn accepted_combinations(node: &Node, ranges: [[u64; 2]; 4]) -> u64 {
+    match node {
+        Node::Accept => ranges
+            .iter()
+            .map(|[low, high]| (high + 1).saturating_sub(*low))
+            .product(),
+        Node::Reject => 0,
+        Node::Branch {
+            category,
+            threshold,
+            below,
+            at_or_above,
+        } => {
+            let [low, high] = ranges[*category];
+
+            let mut below_ranges = ranges;
+            below_ranges[*category] = [low, high.min(threshold - 1)];
+            let mut at_or_above_ranges = ranges;
+            at_or_above_ranges[*category] = [low.max(*threshold), high];
+
+            accepted_combinations(below, below_ranges)
+                + accepted_combinations(at_or_above, at_or_above_ranges)
+        }
+    }
+}
+
+fn branch_count(node: &Node) -> usize {
+    match node {
+        Node::Accept | Node::Reject => 0,
+        Node::Branch {
+            below, at_or_above, ..
+        } => 1 + branch_count(below) + branch_count(at_or_above),
+    }
+}
+
+#[cfg(test)]
+mod decision_tree_tests {
+    use super::super::regions::Workflows;
+    use super::super::{parse_input, pt1, pt1_by_interpreting, pt2};
+    use super::*;
+
+    const EXAMPLE: &str = "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}";
+
+    #[test]
+    fn accepts_the_same_parts_as_the_workflows() {
+        let (workflows_str, _) = EXAMPLE.split_once("\n\n").unwrap();
+        let tree = DecisionTree::load(workflows_str).unwrap();
+        let (_, parts) = parse_input(EXAMPLE);
+
+        let accepted: Vec<_> = parts.iter().map(|part| tree.accepts(part)).collect();
+
+        assert_eq!(vec![true, false, true, false, true], accepted);
+        assert_eq!(19114, pt1(EXAMPLE));
+        assert_eq!(pt1_by_interpreting(EXAMPLE), pt1(EXAMPLE));
+    }
+
+    #[test]
+    fn counts_the_same_combinations_as_the_regions() {
+        let (workflows_str, _) = EXAMPLE.split_once("\n\n").unwrap();
+        let tree = DecisionTree::load(workflows_str).unwrap();
+
+        let regions = Workflows::load(workflows_str)
+            .unwrap()
+            .accepted_regions()
+            .unwrap();
+        let combinations: u64 = regions.iter().map(|region| region.combinations()).sum();
+
+        assert_eq!(167409079868000, tree.accepted_combinations());
+        assert_eq!(combinations, tree.accepted_combinations());
+        assert_eq!(pt2(EXAMPLE), tree.accepted_combinations());
+    }
+
+    #[test]
+    fn keeps_ranges_narrowed_by_rules_that_can_never_match() {
+        let workflows_str = "in{m<3:w1,A}\nw1{m>3368:R,w2}\nw2{a>1:R,m>3:A,R}";
+        let tree = DecisionTree::load(workflows_str).unwrap();
+
+        let regions = Workflows::load(workflows_str)
+            .unwrap()
+            .accepted_regions()
+            .unwrap();
+        let combinations: u64 = regions.iter().map(|region| region.combinations()).sum();
+
+        assert_eq!(255872000000000, tree.accepted_combinations());
+        assert_eq!(combinations, tree.accepted_combinations());
+    }
+
+    #[test]
+    fn removes_redundant_and_unreachable_rules() {
+        // lnx and gd always reach the same outcome, and the second rule of px
+        // can never match once a<2006 has failed
+        let tree = DecisionTree::load(
+            "in{x<10:lnx,gd}\nlnx{m>1548:A,A}\ngd{a>3333:R,px}\npx{a<2006:R,a<1000:A,R}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Node::Branch {
+                category: 0,
+                threshold: 10,
+                below: Box::new(Node::Accept),
+                at_or_above: Box::new(Node::Reject),
+            },
+            tree.root
+        );
+        assert_eq!(1, tree.branch_count());
+    }
+
+    #[test]
+    fn prunes_conditions_already_decided_by_earlier_ones() {
+        let tree = DecisionTree::load("in{x>100:A,x>50:R,x<10:R,A}").unwrap();
+
+        assert_eq!(
+            Node::Branch {
+                category: 0,
+                threshold: 101,
+                below: Box::new(Node::Branch {
+                    category: 0,
+                    threshold: 51,
+                    below: Box::new(Node::Branch {
+                        category: 0,
+                        threshold: 10,
+                        below: Box::new(Node::Reject),
+                        at_or_above: Box::new(Node::Accept),
+                    }),
+                    at_or_above: Box::new(Node::Reject),
+                }),
+                at_or_above: Box::new(Node::Accept),
+            },
+            tree.root
+        );
+
+        let tree = DecisionTree::load("in{x>100:A,x>200:R,R}").unwrap();
+        assert_eq!(1, tree.branch_count());
+    }
+
+    #[test]
+    fn rejects_invalid_workflows() {
+        assert_eq!(
+            WorkflowError::MissingStart,
+            DecisionTree::load("px{A}").unwrap_err()
+        );
+        assert_eq!(
+            WorkflowError::DanglingName {
+                workflow: "in".to_string(),
+                destination: "px".to_string()
+            },
+            DecisionTree::load("in{x<10:px,A}").unwrap_err()
+        );
+        assert_eq!(
+            WorkflowError::Cycle(vec![
+                "in".to_string(),
+                "px".to_string(),
+                "qs".to_string(),
+                "in".to_string()
+            ]),
+            DecisionTree::load("in{x<10:px,A}\npx{qs}\nqs{m>5:in,R}").unwrap_err()
+        );
+        assert_eq!(
+            WorkflowError::NoFallback {
+                workflow: "in".to_string()
+            },
+            DecisionTree::load("in{x<10:A}").unwrap_err()
+        );
+    }
+}
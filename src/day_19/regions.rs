@@ -0,0 +1,331 @@
+use super::decision_tree::{
+    category_index, split, validate, WorkflowError, MAX_RATING, MIN_RATING,
+};
+use super::{parse_workflows, Part, Rule, Workflow};
+use std::collections::HashMap;
+
+const CATEGORIES: [char; 4] = ['x', 'm', 'a', 's'];
+
+// One rule a part or region was checked against, and whether it matched
+#[derive(Debug, PartialEq, Clone)]
+pub struct RuleStep<'a> {
+    pub workflow: &'a str,
+    pub rule_index: usize,
+    pub rule: String,
+    pub matched: bool,
+}
+
+impl RuleStep<'_> {
+    pub fn to_text(&self) -> String {
+        let not = if self.matched { "" } else { "not " };
+        format!("{}[{}] {not}{}", self.workflow, self.rule_index, self.rule)
+    }
+}
+
+// A box of x, m, a and s ratings, inclusive at both ends, that every part in
+// is accepted by following the same rules
+#[derive(Debug, PartialEq)]
+pub struct Region<'a> {
+    pub ranges: [[u64; 2]; 4],
+    pub path: Vec<RuleStep<'a>>,
+}
+
+impl Region<'_> {
+    pub fn combinations(&self) -> u64 {
+        self.ranges
+            .iter()
+            .map(|[low, high]| high + 1 - low)
+            .product()
+    }
+
+    pub fn contains(&self, part: &Part) -> bool {
+        self.ranges
+            .iter()
+            .zip(ratings(part))
+            .all(|([low, high], rating)| *low <= rating && rating <= *high)
+    }
+
+    pub fn to_text(&self) -> String {
+        let ranges: Vec<_> = CATEGORIES
+            .iter()
+            .zip(self.ranges)
+            .map(|(category, [low, high])| format!("{category}={low}..{high}"))
+            .collect();
+        let path: Vec<_> = self.path.iter().map(|step| step.to_text()).collect();
+        format!("{}: {}", ranges.join(" "), path.join(" -> "))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Explanation<'a> {
+    pub accepted: bool,
+    pub path: Vec<RuleStep<'a>>,
+}
+
+// The workflows parsed and checked once, so any number of parts can be
+// explained against them
+#[derive(Debug)]
+pub struct Workflows<'a> {
+    workflows: HashMap<&'a str, Workflow<'a>>,
+}
+
+impl<'a> Workflows<'a> {
+    pub fn load(workflows_str: &'a str) -> Result<Workflows<'a>, WorkflowError> {
+        let workflows = parse_workflows(workflows_str);
+        validate(&workflows)?;
+        Ok(Workflows { workflows })
+    }
+
+    // Splits the whole rating space into disjoint accepted regions, one for
+    // every way through the workflows that ends in A
+    pub fn accepted_regions(&self) -> Result<Vec<Region<'a>>, WorkflowError> {
+        let mut regions = vec![];
+        let mut path = vec![];
+        let ranges = [[MIN_RATING, MAX_RATING]; 4];
+        find_regions(&self.workflows, "in", ranges, &mut path, &mut regions)?;
+        Ok(regions)
+    }
+
+    pub fn explain(&self, part: &Part) -> Result<Explanation<'a>, WorkflowError> {
+        let ratings = ratings(part);
+        let mut path = vec![];
+        let mut workflow_name = "in";
+
+        while !matches!(workflow_name, "A" | "R") {
+            let (name, workflow) = self.workflows.get_key_value(workflow_name).unwrap();
+
+            let rule = workflow
+                .rules
+                .iter()
+                .enumerate()
+                .find(|(rule_index, rule)| {
+                    let matched = matches(rule, ratings);
+                    path.push(step(name, *rule_index, rule, matched));
+                    matched
+                })
+                .map(|(_, rule)| rule)
+                .ok_or_else(|| WorkflowError::NoFallback {
+                    workflow: workflow_name.to_string(),
+                })?;
+
+            workflow_name = rule.destination_workflow;
+        }
+
+        Ok(Explanation {
+            accepted: workflow_name == "A",
+            path,
+        })
+    }
+}
+
+fn ratings(part: &Part) -> [u64; 4] {
+    [part.x, part.m, part.a, part.s]
+}
+
+fn find_regions<'a>(
+    workflows: &HashMap<&'a str, Workflow<'a>>,
+    workflow_name: &'a str,
+    ranges: [[u64; 2]; 4],
+    path: &mut Vec<RuleStep<'a>>,
+    regions: &mut Vec<Region<'a>>,
+) -> Result<(), WorkflowError> {
+    match workflow_name {
+        "A" => {
+            regions.push(Region {
+                ranges,
+                path: path.clone(),
+            });
+            return Ok(());
+        }
+        "R" => return Ok(()),
+        _ => (),
+    }
+
+    let depth = path.len();
+    let mut remaining = Some(ranges);
+
+    for (rule_index, rule) in workflows[workflow_name].rules.iter().enumerate() {
+        let Some(ranges) = remaining else {
+            break;
+        };
+
+        let (matching, not_matching) = split(ranges, rule);
+
+        if let Some(matching) = matching {
+            path.push(step(workflow_name, rule_index, rule, true));
+            find_regions(
+                workflows,
+                rule.destination_workflow,
+                matching,
+                path,
+                regions,
+            )?;
+            path.pop();
+        }
+
+        if not_matching.is_some() {
+            path.push(step(workflow_name, rule_index, rule, false));
+        }
+        remaining = not_matching;
+    }
+
+    path.truncate(depth);
+
+    if remaining.is_some() {
+        return Err(WorkflowError::NoFallback {
+            workflow: workflow_name.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn matches(rule: &Rule, ratings: [u64; 4]) -> bool {
+    let Some(condition) = &rule.condition else {
+        return true;
+    };
+
+    let rating = ratings[category_index(condition.part_property)];
+
+    match condition.operator {
+        '>' => rating > condition.value,
+        '<' => rating < condition.value,
+        _ => panic!("invalid condition operator"),
+    }
+}
+
+fn step<'a>(workflow: &'a str, rule_index: usize, rule: &Rule<'a>, matched: bool) -> RuleStep<'a> {
+    let rule = match &rule.condition {
+        Some(condition) => format!(
+            "{}{}{}:{}",
+            condition.part_property, condition.operator, condition.value, rule.destination_workflow
+        ),
+        None => rule.destination_workflow.to_string(),
+    };
+
+    RuleStep {
+        workflow,
+        rule_index,
+        rule,
+        matched,
+    }
+}
+
+#[cfg(test)]
+mod regions_tests {
+    use super::super::{parse_input, pt2};
+    use super::*;
+
+    const EXAMPLE: &str = "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}";
+
+    fn overlap(a: [u64; 2], b: [u64; 2]) -> bool {
+        a[0] <= b[1] && b[0] <= a[1]
+    }
+
+    #[test]
+    fn regions_are_disjoint_and_add_up_to_pt2() {
+        let (workflows_str, _) = EXAMPLE.split_once("\n\n").unwrap();
+        let regions = Workflows::load(workflows_str)
+            .unwrap()
+            .accepted_regions()
+            .unwrap();
+
+        let combinations: u64 = regions.iter().map(|region| region.combinations()).sum();
+        assert_eq!(pt2(EXAMPLE), combinations);
+        assert_eq!(167409079868000, combinations);
+
+        for (i, first) in regions.iter().enumerate() {
+            for second in &regions[i + 1..] {
+                let (a, b) = (first.ranges, second.ranges);
+                assert!(!(0..4).all(|category| overlap(a[category], b[category])));
+            }
+        }
+    }
+
+    #[test]
+    fn lists_the_path_to_each_region() {
+        let regions = Workflows::load("in{s<1351:px,A}\npx{a<2006:R,x>10:A,R}")
+            .unwrap()
+            .accepted_regions()
+            .unwrap();
+
+        let expected = vec![
+            "x=11..4000 m=1..4000 a=2006..4000 s=1..1350: in[0] s<1351:px -> px[0] not a<2006:R -> px[1] x>10:A",
+            "x=1..4000 m=1..4000 a=1..4000 s=1351..4000: in[0] not s<1351:px -> in[1] A",
+        ];
+        let text: Vec<_> = regions.iter().map(|region| region.to_text()).collect();
+        assert_eq!(expected, text);
+    }
+
+    #[test]
+    fn handles_single_rating_ranges() {
+        let regions = Workflows::load("in{x<2:A,x>2:R,m>3999:A,R}")
+            .unwrap()
+            .accepted_regions()
+            .unwrap();
+
+        let combinations: Vec<_> = regions.iter().map(|region| region.combinations()).collect();
+        assert_eq!(vec![4000 * 4000 * 4000, 4000 * 4000], combinations);
+        assert_eq!([2, 2], regions[1].ranges[0]);
+        assert_eq!([4000, 4000], regions[1].ranges[1]);
+    }
+
+    #[test]
+    fn explains_parts_along_the_same_path_as_their_region() {
+        let (workflows_str, _) = EXAMPLE.split_once("\n\n").unwrap();
+        let (_, parts) = parse_input(EXAMPLE);
+        let workflows = Workflows::load(workflows_str).unwrap();
+        let regions = workflows.accepted_regions().unwrap();
+
+        let accepted: Vec<_> = parts
+            .iter()
+            .map(|part| workflows.explain(part).unwrap().accepted)
+            .collect();
+        assert_eq!(vec![true, false, true, false, true], accepted);
+
+        for part in &parts {
+            let explanation = workflows.explain(part).unwrap();
+            let region = regions.iter().find(|region| region.contains(part));
+
+            assert_eq!(explanation.accepted, region.is_some());
+            if let Some(region) = region {
+                assert_eq!(region.path, explanation.path);
+            }
+        }
+
+        let steps: Vec<_> = workflows
+            .explain(&parts[1])
+            .unwrap()
+            .path
+            .iter()
+            .map(|step| step.to_text())
+            .collect();
+        assert_eq!(
+            vec![
+                "in[0] s<1351:px",
+                "px[0] not a<2006:qkq",
+                "px[1] not m>2090:A",
+                "px[2] rfg",
+                "rfg[0] s<537:gd",
+                "gd[0] not a>3333:R",
+                "gd[1] R",
+            ],
+            steps
+        );
+    }
+}
@@ -1,3 +1,6 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
 use std::fs;
 
 pub fn run() {
@@ -52,6 +55,113 @@ fn predict_next_value(value_history: Vec<i64>) -> i64 {
     *differences.last().unwrap().last().unwrap()
 }
 
+#[derive(Debug, PartialEq)]
+pub enum OasisError {
+    EmptyHistory,
+    NoZeroDifferenceRow,
+}
+
+// Models a value history as the polynomial p(x) that produced it, where x is
+// the index into the history (so p(0) is the first value recorded).
+#[derive(Debug)]
+pub struct OasisModel {
+    history_len: usize,
+    leading_differences: Vec<BigInt>,
+    coefficients: Vec<BigRational>,
+}
+
+impl OasisModel {
+    pub fn fit(value_history: &[i64]) -> Result<OasisModel, OasisError> {
+        if value_history.is_empty() {
+            return Err(OasisError::EmptyHistory);
+        }
+
+        let mut row: Vec<BigInt> = value_history.iter().map(|v| BigInt::from(*v)).collect();
+        let mut leading_differences = vec![];
+
+        while !row.iter().all(|x| x.is_zero()) {
+            if row.len() == 1 {
+                return Err(OasisError::NoZeroDifferenceRow);
+            }
+            leading_differences.push(row[0].clone());
+            row = row.windows(2).map(|pair| &pair[1] - &pair[0]).collect();
+        }
+
+        if leading_differences.is_empty() {
+            leading_differences.push(BigInt::zero());
+        }
+
+        let coefficients = newton_to_power_basis(&leading_differences);
+
+        Ok(OasisModel {
+            history_len: value_history.len(),
+            leading_differences,
+            coefficients,
+        })
+    }
+
+    pub fn degree(&self) -> usize {
+        self.leading_differences.len() - 1
+    }
+
+    // Coefficients in ascending powers of x, i.e. coefficients()[k] is the
+    // coefficient of x^k.
+    pub fn coefficients(&self) -> &[BigRational] {
+        &self.coefficients
+    }
+
+    pub fn value_at(&self, x: i64) -> BigInt {
+        self.value_at_big(&BigInt::from(x))
+    }
+
+    fn value_at_big(&self, x: &BigInt) -> BigInt {
+        let mut binomial = BigInt::one();
+        let mut value = BigInt::zero();
+
+        for (j, difference) in self.leading_differences.iter().enumerate() {
+            value += difference * &binomial;
+            binomial = binomial * (x - j) / (j + 1);
+        }
+
+        value
+    }
+
+    // Positive steps extrapolate forwards from the last value in the history,
+    // negative steps extrapolate backwards from the first.
+    pub fn extrapolate(&self, steps: i64) -> BigInt {
+        if steps >= 0 {
+            // Summed as a BigInt so huge steps can't overflow
+            self.value_at_big(&(BigInt::from(self.history_len - 1) + steps))
+        } else {
+            self.value_at(steps)
+        }
+    }
+}
+
+fn newton_to_power_basis(leading_differences: &[BigInt]) -> Vec<BigRational> {
+    let mut coefficients = vec![BigRational::zero(); leading_differences.len()];
+
+    // x(x - 1)...(x - j + 1) / j!, built up one factor at a time
+    let mut basis = vec![BigRational::one()];
+
+    for (j, difference) in leading_differences.iter().enumerate() {
+        for (k, c) in basis.iter().enumerate() {
+            coefficients[k] += c * BigRational::from_integer(difference.clone());
+        }
+
+        let root = BigRational::from_integer(BigInt::from(j));
+        let divisor = BigRational::from_integer(BigInt::from(j + 1));
+        let mut next = vec![BigRational::zero(); basis.len() + 1];
+        for (k, c) in basis.iter().enumerate() {
+            next[k + 1] += c / &divisor;
+            next[k] -= c * &root / &divisor;
+        }
+        basis = next;
+    }
+
+    coefficients
+}
+
 #[cfg(test)]
 mod predict_next_value_tests {
     use super::*;
@@ -100,3 +210,74 @@ mod predict_previous_value_tests {
         assert_eq!(5, predict_next_value(value_history));
     }
 }
+
+#[cfg(test)]
+mod oasis_model_tests {
+    use super::*;
+
+    fn rational(numer: i64, denom: i64) -> BigRational {
+        BigRational::new(BigInt::from(numer), BigInt::from(denom))
+    }
+
+    #[test]
+    fn extrapolates_example_input_in_both_directions() {
+        let model = OasisModel::fit(&[10, 13, 16, 21, 30, 45]).unwrap();
+        assert_eq!(BigInt::from(68), model.extrapolate(1));
+        assert_eq!(BigInt::from(5), model.extrapolate(-1));
+    }
+
+    #[test]
+    fn reports_degree_and_coefficients() {
+        let model = OasisModel::fit(&[1, 3, 6, 10, 15, 21]).unwrap();
+        assert_eq!(2, model.degree());
+        assert_eq!(
+            vec![rational(1, 1), rational(3, 2), rational(1, 2)],
+            model.coefficients()
+        );
+    }
+
+    #[test]
+    fn constant_and_zero_histories() {
+        let model = OasisModel::fit(&[7, 7, 7]).unwrap();
+        assert_eq!(0, model.degree());
+        assert_eq!(vec![rational(7, 1)], model.coefficients());
+
+        let model = OasisModel::fit(&[0, 0]).unwrap();
+        assert_eq!(0, model.degree());
+        assert_eq!(BigInt::from(0), model.extrapolate(-3));
+    }
+
+    #[test]
+    fn extrapolates_many_steps_without_overflow() {
+        let model = OasisModel::fit(&[0, 1, 8, 27, 64]).unwrap();
+        let steps = 1_000_000_000_000;
+        let x = BigInt::from(4 + steps);
+        assert_eq!(&x * &x * &x, model.extrapolate(steps));
+        assert_eq!(BigInt::from(-27), model.extrapolate(-3));
+
+        let x = BigInt::from(i64::MAX) + 4;
+        assert_eq!(&x * &x * &x, model.extrapolate(i64::MAX));
+    }
+
+    #[test]
+    fn matches_predict_next_value_on_example_input() {
+        for history in [[0, 3, 6, 9, 12, 15], [1, 3, 6, 10, 15, 21]] {
+            let model = OasisModel::fit(&history).unwrap();
+            let expected = predict_next_value(history.to_vec());
+            assert_eq!(BigInt::from(expected), model.extrapolate(1));
+        }
+    }
+
+    #[test]
+    fn flags_sequence_without_zero_difference_row() {
+        assert_eq!(
+            OasisError::NoZeroDifferenceRow,
+            OasisModel::fit(&[1, 2, 4, 8]).unwrap_err()
+        );
+        assert_eq!(
+            OasisError::NoZeroDifferenceRow,
+            OasisModel::fit(&[5]).unwrap_err()
+        );
+        assert_eq!(OasisError::EmptyHistory, OasisModel::fit(&[]).unwrap_err());
+    }
+}
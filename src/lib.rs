@@ -1,14 +1,14 @@
 mod day_1;
-mod day_10;
-mod day_11;
-mod day_12;
-mod day_13;
-mod day_14;
-mod day_15;
-mod day_16;
-mod day_17;
-mod day_18;
-mod day_19;
+pub mod day_10;
+pub mod day_11;
+pub mod day_12;
+pub mod day_13;
+pub mod day_14;
+pub mod day_15;
+pub mod day_16;
+pub mod day_17;
+pub mod day_18;
+pub mod day_19;
 mod day_2;
 mod day_20;
 mod day_21;
@@ -21,7 +21,7 @@ mod day_5;
 mod day_6;
 mod day_7;
 mod day_8;
-mod day_9;
+pub mod day_9;
 
 pub fn run() {
     println!("========== Day 1 ==========");